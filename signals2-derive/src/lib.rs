@@ -0,0 +1,202 @@
+// Copyright Christian Daley 2021
+// Copyright Frank Mori Hess 2007-2008.
+// Distributed under the Boost Software License, Version 1.0. 
+// See http://www.boost.org/LICENSE_1_0.txt
+
+//! # signals2-derive
+//!
+//! Provides the `Signals` derive macro for the [signals2](https://docs.rs/signals2) crate. This crate should not
+//! be used directly, instead enable the `derive` feature of `signals2`.
+
+#![deny(missing_docs)]
+
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::{quote, format_ident};
+use syn::parse::{Parse, ParseStream};
+use syn::punctuated::Punctuated;
+use syn::{parse_macro_input, parse_quote, Data, DeriveInput, Fields, GenericArgument, PathArguments, ReturnType,
+    Token, Type, TypeBareFn};
+
+/// Generates accessors for the fields of a struct that are annotated with `#[signal]`. The type of every annotated
+/// field must be written either as `Signal<...>` or as `signal!(...)`. For each annotated field `name` two methods
+/// are generated:
+///
+/// * `pub fn on_name(&self) -> ConnectHandle<...>`, which allows users of the struct to connect slots to the
+///   signal without being able to emit it.
+/// * `fn emit_name(&self, ...)`, a private method that emits the signal. If the signal's arguments are written as a
+///   tuple the method accepts them individually, otherwise it accepts the tuple.
+///
+/// # Example
+/// ```
+/// use signals2::*;
+///
+/// #[derive(Default, Signals)]
+/// struct Button {
+///     #[signal]
+///     clicked: signal!(fn(i32, i32)),
+///     #[signal]
+///     closing: Signal<(), bool>,
+///     label: String
+/// }
+///
+/// impl Button {
+///     fn click(&self, x: i32, y: i32) {
+///         self.emit_clicked(x, y);
+///     }
+/// }
+///
+/// let button = Button::default();
+/// button.on_clicked().connect(|x, y| println!("clicked at ({}, {})", x, y));
+/// button.click(1, 2); // prints "clicked at (1, 2)"
+/// ```
+#[proc_macro_derive(Signals, attributes(signal))]
+pub fn derive_signals(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand(input)
+        .unwrap_or_else(|err| err.to_compile_error())
+        .into()
+}
+
+// The components of a signal's type, with the defaults filled in.
+struct SignalType {
+    args: Type,
+    ret: Type,
+    combiner: Type,
+    group: Type
+}
+
+// The body of a `signal!(fn(A, B) -> R, C, G)` invocation.
+struct SignalMacro {
+    func: TypeBareFn,
+    rest: Punctuated<Type, Token![,]>
+}
+
+impl Parse for SignalMacro {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let func = input.parse()?;
+        let rest = if input.is_empty() {
+            Punctuated::new()
+        } else {
+            input.parse::<Token![,]>()?;
+            Punctuated::parse_terminated(input)?
+        };
+
+        Ok(SignalMacro { func, rest })
+    }
+}
+
+impl SignalType {
+    fn from_type(ty: &Type) -> syn::Result<Self> {
+        match ty {
+            Type::Paren(paren) => Self::from_type(&paren.elem),
+            Type::Group(group) => Self::from_type(&group.elem),
+            Type::Path(path) if path.qself.is_none() && last_ident_is(&path.path, "Signal") => {
+                let segment = path.path.segments.last().unwrap();
+                let params: Vec<Type> = match &segment.arguments {
+                    PathArguments::AngleBracketed(args) => args.args
+                        .iter()
+                        .filter_map(|arg| match arg {
+                            GenericArgument::Type(ty) => Some(ty.clone()),
+                            _ => None
+                        })
+                        .collect(),
+                    _ => Vec::new()
+                };
+
+                if params.is_empty() {
+                    return Err(syn::Error::new_spanned(ty, "the argument types of the signal must be specified"));
+                }
+
+                // `Signal<(T)>` is the same type as `Signal<T>`, which is almost never what was meant.
+                if let Type::Paren(paren) = &params[0] {
+                    let elem = &paren.elem;
+                    return Err(syn::Error::new_spanned(paren, format!(
+                        "a signal with a single argument must use a one-element tuple, write `({},)` instead",
+                        quote!(#elem))));
+                }
+
+                Ok(Self::with_defaults(params[0].clone(), params.get(1), params.get(2), params.get(3)))
+            },
+            Type::Macro(mac) if last_ident_is(&mac.mac.path, "signal") => {
+                let body: SignalMacro = mac.mac.parse_body()?;
+                let args: Vec<&Type> = body.func.inputs.iter().map(|arg| &arg.ty).collect();
+                let ret = match &body.func.output {
+                    ReturnType::Default => None,
+                    ReturnType::Type(_, ty) => Some(ty.as_ref())
+                };
+
+                let rest: Vec<&Type> = body.rest.iter().collect();
+                Ok(Self::with_defaults(parse_quote!((#(#args,)*)), ret, rest.first().copied(), rest.get(1).copied()))
+            },
+            _ => Err(syn::Error::new_spanned(ty, "#[signal] fields must have a type of the form `Signal<...>` or `signal!(...)`"))
+        }
+    }
+
+    fn with_defaults(args: Type, ret: Option<&Type>, combiner: Option<&Type>, group: Option<&Type>) -> Self {
+        SignalType {
+            args,
+            ret: ret.cloned().unwrap_or_else(|| parse_quote!(())),
+            combiner: combiner.cloned().unwrap_or_else(|| parse_quote!(::signals2::combiner::DefaultCombiner)),
+            group: group.cloned().unwrap_or_else(|| parse_quote!(i32))
+        }
+    }
+}
+
+fn last_ident_is(path: &syn::Path, name: &str) -> bool {
+    path.segments.last().map_or(false, |segment| segment.ident == name)
+}
+
+fn expand(input: DeriveInput) -> syn::Result<TokenStream2> {
+    let fields = match &input.data {
+        Data::Struct(data) => match &data.fields {
+            Fields::Named(fields) => &fields.named,
+            _ => return Err(syn::Error::new_spanned(&input.ident, "Signals can only be derived for structs with named fields"))
+        },
+        _ => return Err(syn::Error::new_spanned(&input.ident, "Signals can only be derived for structs"))
+    };
+
+    let mut methods = Vec::new();
+    for field in fields.iter() {
+        if !field.attrs.iter().any(|attr| attr.path().is_ident("signal")) {
+            continue;
+        }
+
+        let name = field.ident.as_ref().unwrap();
+        let SignalType { args, ret, combiner, group } = SignalType::from_type(&field.ty)?;
+        let on_name = format_ident!("on_{}", name);
+        let emit_name = format_ident!("emit_{}", name);
+        let on_doc = format!("Returns a `ConnectHandle` that can be used to connect slots to the `{}` signal.", name);
+        let emit_doc = format!("Emits the `{}` signal.", name);
+
+        let (params, values) = match &args {
+            Type::Tuple(tuple) => {
+                let values: Vec<_> = (0..tuple.elems.len()).map(|i| format_ident!("arg{}", i)).collect();
+                let types = tuple.elems.iter();
+                (quote!(#(#values: #types),*), quote!((#(#values,)*)))
+            },
+            _ => (quote!(args: #args), quote!(args))
+        };
+
+        methods.push(quote! {
+            #[doc = #on_doc]
+            pub fn #on_name(&self) -> ::signals2::ConnectHandle<#args, #ret, #combiner, #group> {
+                self.#name.get_connect_handle()
+            }
+
+            #[doc = #emit_doc]
+            #[allow(dead_code, clippy::too_many_arguments)]
+            fn #emit_name(&self, #params) -> <#combiner as ::signals2::combiner::Combiner<#ret>>::Output {
+                ::signals2::Emit::emit_tuple(&self.#name, #values)
+            }
+        });
+    }
+
+    let ident = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    Ok(quote! {
+        impl #impl_generics #ident #ty_generics #where_clause {
+            #(#methods)*
+        }
+    })
+}
@@ -0,0 +1,318 @@
+// Copyright Christian Daley 2021
+// Copyright Frank Mori Hess 2007-2008.
+// Distributed under the Boost Software License, Version 1.0. 
+// See http://www.boost.org/LICENSE_1_0.txt
+
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt;
+use std::io;
+use std::net::Shutdown;
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, mpsc::{self, SyncSender, TrySendError}, atomic::{AtomicBool, Ordering}};
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+use serde::{Serialize, de::DeserializeOwned};
+
+use crate::{Signal, ConnectHandle, Group, Position, ScopedConnection, Emit, Emit1};
+use crate::combiner::Combiner;
+use crate::record::{read_frame, write_frame};
+
+// How long a write to the receiver may make no progress before the connection is considered lost.
+const WRITE_TIMEOUT: Duration = Duration::from_secs(1);
+
+// How long the receiver waits between checks for new bridges and for its own shutdown.
+const ACCEPT_POLL_INTERVAL: Duration = Duration::from_millis(20);
+
+/// Errors reported by the error signals of [SignalBridge] and [BridgeReceiver].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum BridgeError {
+    /// Sending an emission to the peer failed, usually because the peer went away. The bridge reconnects and sends
+    /// the emission again.
+    Disconnected(io::ErrorKind),
+    /// An emission was dropped because the send queue was full.
+    QueueFull,
+    /// The arguments of an emission could not be encoded or decoded.
+    Encoding(String)
+}
+
+impl fmt::Display for BridgeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BridgeError::Disconnected(kind) => write!(f, "the peer disconnected: {:?}", kind),
+            BridgeError::QueueFull => write!(f, "the send queue is full"),
+            BridgeError::Encoding(err) => write!(f, "encoding error: {}", err)
+        }
+    }
+}
+
+impl Error for BridgeError {}
+
+/// Forwards the emissions of a local signal to a [BridgeReceiver] in another process over a Unix domain socket.
+/// Requires the `serde` feature.
+///
+/// The bridge is connected to the signal at the back of [Group::Back] and does not contribute to the signal's return
+/// value. Every emission is encoded with bincode and placed in a bounded queue, which is drained by a background thread
+/// that writes to the socket. If the receiver is not listening yet, or goes away, the background thread keeps trying to
+/// connect at the given interval and emissions accumulate in the queue. Emissions that do not fit in the queue are
+/// dropped. A receiver that stops reading for a second is treated as a lost connection. Both dropped emissions and lost
+/// connections are reported by the signal returned from [on_error](SignalBridge::on_error).
+///
+/// When the bridge is dropped it disconnects from the signal and sends the emissions that are still queued, unless it
+/// is not connected to a receiver or the connection is lost while sending them.
+/// # Example
+/// ```
+/// use signals2::*;
+/// use signals2::bridge::{SignalBridge, BridgeReceiver};
+/// use std::sync::mpsc;
+/// use std::time::Duration;
+///
+/// let path = std::env::temp_dir().join(format!("signals2-doc-{}.sock", std::process::id()));
+/// let remote: Signal<(i32, String)> = Signal::new();
+/// let receiver = BridgeReceiver::bind(&path, remote.get_emit_handle()).unwrap();
+/// let rx = remote.receiver();
+///
+/// let local: Signal<(i32, String)> = Signal::new();
+/// let bridge = SignalBridge::new(&local, &path, 16, Duration::from_millis(10));
+/// local.emit(1, "one".to_string());
+///
+/// assert_eq!(rx.recv_timeout(Duration::from_secs(5)), Ok((1, "one".to_string())));
+/// ```
+pub struct SignalBridge {
+    error_sig: Signal<(BridgeError,)>,
+    sender: Arc<Mutex<Option<SyncSender<Vec<u8>>>>>,
+    shutdown: Arc<AtomicBool>,
+    thread: Option<JoinHandle<()>>,
+    _conn: ScopedConnection
+}
+
+impl SignalBridge {
+    /// Creates a bridge that forwards the emissions of `sig` to the receiver listening at `path`. At most
+    /// `queue_capacity` emissions are queued while the receiver is unavailable, and connecting to the receiver is retried
+    /// every `reconnect_interval`.
+    ///
+    /// # Panics
+    /// Panics if `queue_capacity` is 0.
+    pub fn new<Args, R, C, G, P>(sig: &Signal<Args, R, C, G>, path: P, queue_capacity: usize, reconnect_interval: Duration) -> Self
+    where
+        Args: Serialize + Clone + 'static,
+        R: 'static,
+        C: Combiner<R> + 'static,
+        G: Ord + Send + Sync + 'static,
+        P: AsRef<Path>
+    {
+        assert!(queue_capacity > 0, "the queue capacity of a SignalBridge must be at least 1");
+        let error_sig = Signal::new();
+        let (sender, receiver) = mpsc::sync_channel(queue_capacity);
+        let sender = Arc::new(Mutex::new(Some(sender)));
+        let shutdown = Arc::new(AtomicBool::new(false));
+
+        let slot_sender = sender.clone();
+        let slot_errors = error_sig.get_emit_handle();
+        let conn = sig.connect_partial(move |_, args| {
+            let err = match bincode::serialize(&args) {
+                Ok(frame) => match slot_sender.lock().unwrap().as_ref().map(|sender| sender.try_send(frame)) {
+                    Some(Err(TrySendError::Full(_))) => Some(BridgeError::QueueFull),
+                    _ => None
+                },
+                Err(err) => Some(BridgeError::Encoding(err.to_string()))
+            };
+
+            if let Some(err) = err {
+                slot_errors.emit(err);
+            }
+
+            None
+        }, Group::Back, Position::Back).scoped();
+
+        let path = path.as_ref().to_path_buf();
+        let errors = error_sig.get_emit_handle();
+        let thread_shutdown = shutdown.clone();
+        let thread = thread::spawn(move || {
+            let mut stream: Option<UnixStream> = None;
+            while let Ok(frame) = receiver.recv() {
+                loop {
+                    if stream.is_none() {
+                        match UnixStream::connect(&path) {
+                            Ok(connected) => {
+                                let _ = connected.set_write_timeout(Some(WRITE_TIMEOUT));
+                                stream = Some(connected);
+                            },
+                            Err(_) if thread_shutdown.load(Ordering::SeqCst) => return,
+                            Err(_) => {
+                                thread::sleep(reconnect_interval);
+                                continue;
+                            }
+                        }
+                    }
+
+                    match write_frame(stream.as_mut().unwrap(), &frame) {
+                        Ok(()) => break,
+                        Err(err) => {
+                            stream = None;
+                            errors.emit(BridgeError::Disconnected(err.kind()));
+                            if thread_shutdown.load(Ordering::SeqCst) {
+                                return;
+                            }
+                        }
+                    }
+                }
+            }
+        });
+
+        SignalBridge {
+            error_sig,
+            sender,
+            shutdown,
+            thread: Some(thread),
+            _conn: conn
+        }
+    }
+
+    /// Returns a handle to the signal that reports errors. The signal is emitted by the background thread when the
+    /// connection to the receiver is lost, and by the emitting thread when an emission is dropped.
+    pub fn on_error(&self) -> ConnectHandle<(BridgeError,)> {
+        self.error_sig.get_connect_handle()
+    }
+}
+
+impl Drop for SignalBridge {
+    fn drop(&mut self) {
+        self.shutdown.store(true, Ordering::SeqCst);
+        self.sender.lock().unwrap().take();
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+// A bridge connected to a receiver: a handle to its stream and the thread reading from it.
+type Connection = (UnixStream, JoinHandle<()>);
+
+/// Receives the emissions forwarded by [SignalBridges](SignalBridge) and re-emits them locally. Requires the `serde`
+/// feature.
+///
+/// The receiver listens on a Unix domain socket and accepts any number of bridges. Every decoded emission is emitted
+/// on the target, which can be anything that implements [Emit], such as an [EmitHandle](crate::EmitHandle). Emissions
+/// are re-emitted on the receiver's background threads. When the receiver is dropped it closes all of its connections
+/// and removes the socket file.
+pub struct BridgeReceiver {
+    path: PathBuf,
+    error_sig: Signal<(BridgeError,)>,
+    shutdown: Arc<AtomicBool>,
+    connections: Arc<Mutex<HashMap<u64, Connection>>>,
+    thread: Option<JoinHandle<()>>
+}
+
+impl BridgeReceiver {
+    /// Listens for bridges at `path` and re-emits the emissions they forward on `target`. Fails if a socket cannot be
+    /// bound at `path`, for example because the file already exists.
+    pub fn bind<Args, E, P>(path: P, target: E) -> io::Result<Self>
+    where
+        Args: DeserializeOwned + Clone + 'static,
+        E: Emit<Args> + Send + Sync + 'static,
+        P: AsRef<Path>
+    {
+        let path = path.as_ref().to_path_buf();
+        let listener = UnixListener::bind(&path)?;
+        // the listener is polled so that shutting down does not depend on the socket file still being reachable
+        listener.set_nonblocking(true)?;
+        let error_sig: Signal<(BridgeError,)> = Signal::new();
+        let shutdown = Arc::new(AtomicBool::new(false));
+        let connections = Arc::new(Mutex::new(HashMap::new()));
+
+        let target = Arc::new(target);
+        let errors = Arc::new(error_sig.get_emit_handle());
+        let thread_shutdown = shutdown.clone();
+        let thread_connections = connections.clone();
+        let thread = thread::spawn(move || {
+            let mut next_id = 0u64;
+            while !thread_shutdown.load(Ordering::SeqCst) {
+                let mut stream = match listener.accept() {
+                    Ok((stream, _)) => stream,
+                    Err(_) => {
+                        thread::sleep(ACCEPT_POLL_INTERVAL);
+                        continue;
+                    }
+                };
+
+                if stream.set_nonblocking(false).is_err() {
+                    continue;
+                }
+
+                let id = next_id;
+                next_id += 1;
+
+                let clone = match stream.try_clone() {
+                    Ok(clone) => clone,
+                    Err(_) => continue
+                };
+
+                let target = target.clone();
+                let errors = errors.clone();
+                let reader_connections = thread_connections.clone();
+
+                // the lock is held until the connection has been added, so the reader cannot remove it too early
+                let mut connections = thread_connections.lock().unwrap();
+                let reader = thread::spawn(move || {
+                    while let Ok(Some(frame)) = read_frame(&mut stream) {
+                        match bincode::deserialize::<Args>(&frame) {
+                            Ok(args) => { target.emit_tuple(args); },
+                            Err(err) => { errors.emit(BridgeError::Encoding(err.to_string())); }
+                        }
+                    }
+
+                    reader_connections.lock().unwrap().remove(&id);
+                });
+
+                connections.insert(id, (clone, reader));
+            }
+        });
+
+        Ok(BridgeReceiver {
+            path,
+            error_sig,
+            shutdown,
+            connections,
+            thread: Some(thread)
+        })
+    }
+
+    /// Returns a handle to the signal that reports emissions that could not be decoded.
+    pub fn on_error(&self) -> ConnectHandle<(BridgeError,)> {
+        self.error_sig.get_connect_handle()
+    }
+
+    /// Returns the number of bridges that are currently connected to the receiver.
+    pub fn connection_count(&self) -> usize {
+        self.connections.lock().unwrap().len()
+    }
+
+    /// Returns the path of the socket the receiver is listening on.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl Drop for BridgeReceiver {
+    fn drop(&mut self) {
+        self.shutdown.store(true, Ordering::SeqCst);
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+
+        // the readers remove their own connections, so the lock must not be held while joining them
+        let connections: Vec<_> = self.connections.lock().unwrap().drain().map(|(_, connection)| connection).collect();
+        for (stream, _) in &connections {
+            let _ = stream.shutdown(Shutdown::Both);
+        }
+
+        for (_, reader) in connections {
+            let _ = reader.join();
+        }
+
+        let _ = std::fs::remove_file(&self.path);
+    }
+}
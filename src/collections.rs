@@ -0,0 +1,596 @@
+// Copyright Christian Daley 2021
+// Copyright Frank Mori Hess 2007-2008.
+// Distributed under the Boost Software License, Version 1.0. 
+// See http://www.boost.org/LICENSE_1_0.txt
+
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::{Arc, Condvar, Mutex, PoisonError, RwLock};
+use std::thread::{self, ThreadId};
+
+use crate::{Signal, ConnectHandle};
+use crate::emit::Emit1;
+
+// Serializes the mutations of a collection together with the emission of their changes, so that changes are emitted
+// in the order in which they were applied. The lock is reentrant so that a slot may mutate the collection whose change
+// it is handling.
+#[derive(Default)]
+struct EmitOrder {
+    owner: Mutex<Option<(ThreadId, usize)>>,
+    released: Condvar
+}
+
+impl EmitOrder {
+    fn lock(&self) -> EmitOrderGuard<'_> {
+        let current = thread::current().id();
+        let mut owner = self.owner.lock().unwrap_or_else(PoisonError::into_inner);
+        loop {
+            match &mut *owner {
+                Some((id, depth)) if *id == current => {
+                    *depth += 1;
+                    break;
+                },
+                Some(_) => owner = self.released.wait(owner).unwrap_or_else(PoisonError::into_inner),
+                None => {
+                    *owner = Some((current, 1));
+                    break;
+                }
+            }
+        }
+
+        EmitOrderGuard { order: self }
+    }
+}
+
+struct EmitOrderGuard<'a> {
+    order: &'a EmitOrder
+}
+
+impl Drop for EmitOrderGuard<'_> {
+    fn drop(&mut self) {
+        let mut owner = self.order.owner.lock().unwrap_or_else(PoisonError::into_inner);
+        if let Some((_, depth)) = &mut *owner {
+            *depth -= 1;
+            if *depth == 0 {
+                *owner = None;
+                self.order.released.notify_all();
+            }
+        }
+    }
+}
+
+/// A change made to an [ObservableVec].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum VecChange<T> {
+    /// `value` was inserted at `index`.
+    Insert {
+        /// The index the value was inserted at.
+        index: usize,
+        /// The inserted value.
+        value: T
+    },
+    /// `value` was removed from `index`.
+    Remove {
+        /// The index the value was removed from.
+        index: usize,
+        /// The removed value.
+        value: T
+    },
+    /// The value at `index` was replaced.
+    Replace {
+        /// The index of the replaced value.
+        index: usize,
+        /// The value before it was replaced.
+        old: T,
+        /// The value after it was replaced.
+        new: T
+    },
+    /// The value at `from` was moved to `to`. The values in between were shifted by one position to make room.
+    Move {
+        /// The index the value was moved from.
+        from: usize,
+        /// The index the value was moved to.
+        to: usize
+    },
+    /// The vector was cleared.
+    Clear {
+        /// The values the vector held before it was cleared.
+        values: Vec<T>
+    },
+    /// The changes made by a single [transaction](ObservableVec::transaction), in the order they were made.
+    Batch(Vec<VecChange<T>>)
+}
+
+/// The mutations that can be made to an [ObservableVec] inside of a [transaction](ObservableVec::transaction).
+/// Every mutation is recorded and emitted as part of one [VecChange::Batch] once the transaction completes.
+pub struct VecTransaction<'a, T> {
+    items: &'a mut Vec<T>,
+    changes: Vec<VecChange<T>>
+}
+
+impl<'a, T> VecTransaction<'a, T>
+where
+    T: Clone
+{
+    /// Returns the current contents of the vector, including changes made earlier in the transaction.
+    pub fn as_slice(&self) -> &[T] {
+        self.items
+    }
+
+    /// Appends `value` to the back of the vector.
+    pub fn push(&mut self, value: T) {
+        let index = self.items.len();
+        self.insert(index, value);
+    }
+
+    /// Removes the last value from the vector and returns it, or `None` if the vector is empty.
+    pub fn pop(&mut self) -> Option<T> {
+        match self.items.len() {
+            0 => None,
+            len => Some(self.remove(len - 1))
+        }
+    }
+
+    /// Inserts `value` at `index`. Panics if `index > len`.
+    pub fn insert(&mut self, index: usize, value: T) {
+        self.items.insert(index, value.clone());
+        self.changes.push(VecChange::Insert { index, value });
+    }
+
+    /// Removes and returns the value at `index`. Panics if `index` is out of bounds.
+    pub fn remove(&mut self, index: usize) -> T {
+        let value = self.items.remove(index);
+        self.changes.push(VecChange::Remove { index, value: value.clone() });
+        value
+    }
+
+    /// Replaces the value at `index` with `value` and returns the old value. Panics if `index` is out of bounds.
+    pub fn replace(&mut self, index: usize, value: T) -> T {
+        let old = std::mem::replace(&mut self.items[index], value.clone());
+        self.changes.push(VecChange::Replace { index, old: old.clone(), new: value });
+        old
+    }
+
+    /// Moves the value at `from` to `to`, shifting the values in between. Nothing is recorded if `from == to`.
+    /// Panics without modifying the vector if either index is out of bounds.
+    pub fn move_item(&mut self, from: usize, to: usize) {
+        let len = self.items.len();
+        assert!(from < len && to < len, "move index out of bounds: the len is {} but the move is from {} to {}", len, from, to);
+        if from == to {
+            return;
+        }
+
+        let value = self.items.remove(from);
+        self.items.insert(to, value);
+        self.changes.push(VecChange::Move { from, to });
+    }
+
+    /// Removes all values from the vector. Nothing is recorded if the vector is already empty.
+    pub fn clear(&mut self) {
+        if !self.items.is_empty() {
+            let values = std::mem::take(self.items);
+            self.changes.push(VecChange::Clear { values });
+        }
+    }
+}
+
+/// A vector that emits a [VecChange] describing every mutation made to it. Mutations are normally emitted one
+/// at a time, but several mutations can be grouped into a [transaction](ObservableVec::transaction) that emits
+/// them as a single [VecChange::Batch].
+///
+/// An `ObservableVec` is a handle to its contents. Cloning it results in two handles to the same vector. Changes are
+/// emitted in the order in which they were applied, even when the vector is mutated from several threads.
+/// # Example
+/// ```
+/// use signals2::*;
+/// use signals2::collections::{ObservableVec, VecChange};
+///
+/// let vec = ObservableVec::new();
+/// vec.changed().connect(|change: VecChange<&str>| println!("{:?}", change));
+/// vec.push("a"); // prints "Insert { index: 0, value: "a" }"
+/// vec.transaction(|tx| {
+///     tx.push("b");
+///     tx.move_item(1, 0);
+/// }); // prints "Batch([Insert { index: 1, value: "b" }, Move { from: 1, to: 0 }])"
+/// assert_eq!(vec.to_vec(), vec!("b", "a"));
+/// ```
+pub struct ObservableVec<T>
+where
+    T: Clone + Send + Sync + 'static
+{
+    items: Arc<RwLock<Vec<T>>>,
+    order: Arc<EmitOrder>,
+    changed: Signal<(VecChange<T>,)>
+}
+
+impl<T> Clone for ObservableVec<T>
+where
+    T: Clone + Send + Sync + 'static
+{
+    /// Creates another handle to the same vector.
+    fn clone(&self) -> Self {
+        Self {
+            items: self.items.clone(),
+            order: self.order.clone(),
+            changed: self.changed.clone()
+        }
+    }
+}
+
+impl<T> Default for ObservableVec<T>
+where
+    T: Clone + Send + Sync + 'static
+{
+    /// Creates an empty vector.
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> From<Vec<T>> for ObservableVec<T>
+where
+    T: Clone + Send + Sync + 'static
+{
+    fn from(items: Vec<T>) -> Self {
+        ObservableVec {
+            items: Arc::new(RwLock::new(items)),
+            order: Arc::new(EmitOrder::default()),
+            changed: Signal::new()
+        }
+    }
+}
+
+impl<T> ObservableVec<T>
+where
+    T: Clone + Send + Sync + 'static
+{
+    /// Creates an empty vector.
+    pub fn new() -> Self {
+        Self::from(Vec::new())
+    }
+
+    /// Returns a [ConnectHandle] for the signal that is emitted after every mutation of the vector.
+    pub fn changed(&self) -> ConnectHandle<(VecChange<T>,)> {
+        self.changed.get_connect_handle()
+    }
+
+    /// Returns the number of values in the vector.
+    pub fn len(&self) -> usize {
+        self.items.read().unwrap().len()
+    }
+
+    /// Returns true if the vector holds no values.
+    pub fn is_empty(&self) -> bool {
+        self.items.read().unwrap().is_empty()
+    }
+
+    /// Returns a clone of the value at `index`, or `None` if `index` is out of bounds.
+    pub fn get(&self, index: usize) -> Option<T> {
+        self.items.read().unwrap().get(index).cloned()
+    }
+
+    /// Returns a clone of the vector's contents.
+    pub fn to_vec(&self) -> Vec<T> {
+        self.items.read().unwrap().clone()
+    }
+
+    /// Appends `value` to the back of the vector.
+    pub fn push(&self, value: T) {
+        self.apply(false, |tx| tx.push(value))
+    }
+
+    /// Removes the last value from the vector and returns it, or `None` if the vector is empty.
+    pub fn pop(&self) -> Option<T> {
+        self.apply(false, |tx| tx.pop())
+    }
+
+    /// Inserts `value` at `index`. Panics if `index > len`.
+    pub fn insert(&self, index: usize, value: T) {
+        self.apply(false, |tx| tx.insert(index, value))
+    }
+
+    /// Removes and returns the value at `index`. Panics if `index` is out of bounds.
+    pub fn remove(&self, index: usize) -> T {
+        self.apply(false, |tx| tx.remove(index))
+    }
+
+    /// Replaces the value at `index` with `value` and returns the old value. Panics if `index` is out of bounds.
+    pub fn replace(&self, index: usize, value: T) -> T {
+        self.apply(false, |tx| tx.replace(index, value))
+    }
+
+    /// Moves the value at `from` to `to`, shifting the values in between. Nothing is emitted if `from == to`.
+    /// Panics without modifying the vector if either index is out of bounds.
+    pub fn move_item(&self, from: usize, to: usize) {
+        self.apply(false, |tx| tx.move_item(from, to))
+    }
+
+    /// Removes all values from the vector. Nothing is emitted if the vector is already empty.
+    pub fn clear(&self) {
+        self.apply(false, |tx| tx.clear())
+    }
+
+    /// Makes several mutations to the vector and emits them as one [VecChange::Batch] once `f` returns.
+    /// Nothing is emitted if `f` makes no mutations. The vector is locked while `f` runs, so `f` must not
+    /// access the vector other than through the given [VecTransaction]. If `f` panics, the mutations it made before
+    /// panicking are kept and emitted before the panic is resumed.
+    pub fn transaction<F, R>(&self, f: F) -> R
+    where
+        F: FnOnce(&mut VecTransaction<T>) -> R
+    {
+        self.apply(true, f)
+    }
+
+    fn apply<F, R>(&self, batch: bool, f: F) -> R
+    where
+        F: FnOnce(&mut VecTransaction<T>) -> R
+    {
+        let _order = self.order.lock();
+        let mut lock = self.items.write().unwrap();
+        let mut tx = VecTransaction {
+            items: &mut lock,
+            changes: Vec::new()
+        };
+
+        // a panic in `f` must not poison the lock, and the changes made before it must still be emitted
+        let res = panic::catch_unwind(AssertUnwindSafe(|| f(&mut tx)));
+        let changes = tx.changes;
+        std::mem::drop(lock);
+
+        if batch {
+            if !changes.is_empty() {
+                self.changed.emit(VecChange::Batch(changes));
+            }
+        } else {
+            for change in changes {
+                self.changed.emit(change);
+            }
+        }
+
+        res.unwrap_or_else(|err| panic::resume_unwind(err))
+    }
+}
+
+/// A change made to an [ObservableMap].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum MapChange<K, V> {
+    /// `value` was inserted under `key`, which was not present before.
+    Insert {
+        /// The inserted key.
+        key: K,
+        /// The inserted value.
+        value: V
+    },
+    /// `key` and its value were removed.
+    Remove {
+        /// The removed key.
+        key: K,
+        /// The value that was stored under the key.
+        value: V
+    },
+    /// The value stored under `key` was replaced.
+    Replace {
+        /// The key whose value was replaced.
+        key: K,
+        /// The value before it was replaced.
+        old: V,
+        /// The value after it was replaced.
+        new: V
+    },
+    /// The map was cleared.
+    Clear {
+        /// The entries the map held before it was cleared, in arbitrary order.
+        entries: Vec<(K, V)>
+    },
+    /// The changes made by a single [transaction](ObservableMap::transaction), in the order they were made.
+    Batch(Vec<MapChange<K, V>>)
+}
+
+/// The mutations that can be made to an [ObservableMap] inside of a [transaction](ObservableMap::transaction).
+/// Every mutation is recorded and emitted as part of one [MapChange::Batch] once the transaction completes.
+pub struct MapTransaction<'a, K, V> {
+    entries: &'a mut HashMap<K, V>,
+    changes: Vec<MapChange<K, V>>
+}
+
+impl<'a, K, V> MapTransaction<'a, K, V>
+where
+    K: Clone + Eq + Hash,
+    V: Clone
+{
+    /// Returns the current contents of the map, including changes made earlier in the transaction.
+    pub fn as_map(&self) -> &HashMap<K, V> {
+        self.entries
+    }
+
+    /// Inserts `value` under `key` and returns the value that was previously stored under `key`, if any.
+    pub fn insert(&mut self, key: K, value: V) -> Option<V> {
+        let old = self.entries.insert(key.clone(), value.clone());
+        self.changes.push(match &old {
+            Some(old) => MapChange::Replace { key, old: old.clone(), new: value },
+            None => MapChange::Insert { key, value }
+        });
+
+        old
+    }
+
+    /// Removes `key` and returns the value that was stored under it, if any.
+    pub fn remove(&mut self, key: &K) -> Option<V> {
+        let (key, value) = self.entries.remove_entry(key)?;
+        self.changes.push(MapChange::Remove { key, value: value.clone() });
+        Some(value)
+    }
+
+    /// Removes all entries from the map. Nothing is recorded if the map is already empty.
+    pub fn clear(&mut self) {
+        if !self.entries.is_empty() {
+            let entries = self.entries.drain().collect();
+            self.changes.push(MapChange::Clear { entries });
+        }
+    }
+}
+
+/// A hash map that emits a [MapChange] describing every mutation made to it. Mutations are normally emitted one
+/// at a time, but several mutations can be grouped into a [transaction](ObservableMap::transaction) that emits
+/// them as a single [MapChange::Batch].
+///
+/// An `ObservableMap` is a handle to its contents. Cloning it results in two handles to the same map. Changes are
+/// emitted in the order in which they were applied, even when the map is mutated from several threads.
+/// # Example
+/// ```
+/// use signals2::*;
+/// use signals2::collections::{ObservableMap, MapChange};
+///
+/// let map = ObservableMap::new();
+/// map.changed().connect(|change: MapChange<&str, i32>| println!("{:?}", change));
+/// map.insert("a", 1); // prints "Insert { key: "a", value: 1 }"
+/// map.insert("a", 2); // prints "Replace { key: "a", old: 1, new: 2 }"
+/// assert_eq!(map.get(&"a"), Some(2));
+/// ```
+pub struct ObservableMap<K, V>
+where
+    K: Clone + Eq + Hash + Send + Sync + 'static,
+    V: Clone + Send + Sync + 'static
+{
+    entries: Arc<RwLock<HashMap<K, V>>>,
+    order: Arc<EmitOrder>,
+    changed: Signal<(MapChange<K, V>,)>
+}
+
+impl<K, V> Clone for ObservableMap<K, V>
+where
+    K: Clone + Eq + Hash + Send + Sync + 'static,
+    V: Clone + Send + Sync + 'static
+{
+    /// Creates another handle to the same map.
+    fn clone(&self) -> Self {
+        Self {
+            entries: self.entries.clone(),
+            order: self.order.clone(),
+            changed: self.changed.clone()
+        }
+    }
+}
+
+impl<K, V> Default for ObservableMap<K, V>
+where
+    K: Clone + Eq + Hash + Send + Sync + 'static,
+    V: Clone + Send + Sync + 'static
+{
+    /// Creates an empty map.
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K, V> From<HashMap<K, V>> for ObservableMap<K, V>
+where
+    K: Clone + Eq + Hash + Send + Sync + 'static,
+    V: Clone + Send + Sync + 'static
+{
+    fn from(entries: HashMap<K, V>) -> Self {
+        ObservableMap {
+            entries: Arc::new(RwLock::new(entries)),
+            order: Arc::new(EmitOrder::default()),
+            changed: Signal::new()
+        }
+    }
+}
+
+impl<K, V> ObservableMap<K, V>
+where
+    K: Clone + Eq + Hash + Send + Sync + 'static,
+    V: Clone + Send + Sync + 'static
+{
+    /// Creates an empty map.
+    pub fn new() -> Self {
+        Self::from(HashMap::new())
+    }
+
+    /// Returns a [ConnectHandle] for the signal that is emitted after every mutation of the map.
+    pub fn changed(&self) -> ConnectHandle<(MapChange<K, V>,)> {
+        self.changed.get_connect_handle()
+    }
+
+    /// Returns the number of entries in the map.
+    pub fn len(&self) -> usize {
+        self.entries.read().unwrap().len()
+    }
+
+    /// Returns true if the map holds no entries.
+    pub fn is_empty(&self) -> bool {
+        self.entries.read().unwrap().is_empty()
+    }
+
+    /// Returns a clone of the value stored under `key`, if any.
+    pub fn get(&self, key: &K) -> Option<V> {
+        self.entries.read().unwrap().get(key).cloned()
+    }
+
+    /// Returns true if the map holds a value for `key`.
+    pub fn contains_key(&self, key: &K) -> bool {
+        self.entries.read().unwrap().contains_key(key)
+    }
+
+    /// Returns a clone of the map's contents.
+    pub fn to_map(&self) -> HashMap<K, V> {
+        self.entries.read().unwrap().clone()
+    }
+
+    /// Inserts `value` under `key` and returns the value that was previously stored under `key`, if any.
+    pub fn insert(&self, key: K, value: V) -> Option<V> {
+        self.apply(false, |tx| tx.insert(key, value))
+    }
+
+    /// Removes `key` and returns the value that was stored under it, if any. Nothing is emitted if
+    /// the key was not present.
+    pub fn remove(&self, key: &K) -> Option<V> {
+        self.apply(false, |tx| tx.remove(key))
+    }
+
+    /// Removes all entries from the map. Nothing is emitted if the map is already empty.
+    pub fn clear(&self) {
+        self.apply(false, |tx| tx.clear())
+    }
+
+    /// Makes several mutations to the map and emits them as one [MapChange::Batch] once `f` returns.
+    /// Nothing is emitted if `f` makes no mutations. The map is locked while `f` runs, so `f` must not
+    /// access the map other than through the given [MapTransaction]. If `f` panics, the mutations it made before
+    /// panicking are kept and emitted before the panic is resumed.
+    pub fn transaction<F, R>(&self, f: F) -> R
+    where
+        F: FnOnce(&mut MapTransaction<K, V>) -> R
+    {
+        self.apply(true, f)
+    }
+
+    fn apply<F, R>(&self, batch: bool, f: F) -> R
+    where
+        F: FnOnce(&mut MapTransaction<K, V>) -> R
+    {
+        let _order = self.order.lock();
+        let mut lock = self.entries.write().unwrap();
+        let mut tx = MapTransaction {
+            entries: &mut lock,
+            changes: Vec::new()
+        };
+
+        // a panic in `f` must not poison the lock, and the changes made before it must still be emitted
+        let res = panic::catch_unwind(AssertUnwindSafe(|| f(&mut tx)));
+        let changes = tx.changes;
+        std::mem::drop(lock);
+
+        if batch {
+            if !changes.is_empty() {
+                self.changed.emit(MapChange::Batch(changes));
+            }
+        } else {
+            for change in changes {
+                self.changed.emit(change);
+            }
+        }
+
+        res.unwrap_or_else(|err| panic::resume_unwind(err))
+    }
+}
@@ -0,0 +1,235 @@
+// Copyright Christian Daley 2021
+// Copyright Frank Mori Hess 2007-2008.
+// Distributed under the Boost Software License, Version 1.0. 
+// See http://www.boost.org/LICENSE_1_0.txt
+
+use std::cell::RefCell;
+use std::sync::{Arc, Mutex};
+
+use crate::{Signal, ConnectHandle, ScopedConnection};
+use crate::connect::Connect2;
+use crate::emit::Emit2;
+
+type Notify = Arc<dyn Fn() + Send + Sync>;
+
+// An observable value that was read while a `Computed` was being evaluated.
+struct Dependency {
+    id: usize,
+    subscribe: Box<dyn FnOnce(Notify) -> ScopedConnection>
+}
+
+thread_local! {
+    // One frame of dependencies for every `Computed` that is currently being evaluated on this thread.
+    static TRACKING: RefCell<Vec<Vec<Dependency>>> = const { RefCell::new(Vec::new()) };
+}
+
+// Records that the observable value identified by `id` was read. If a `Computed` is currently being evaluated on
+// this thread, `subscribe` will later be used to connect a slot that notifies it whenever the value changes.
+pub(crate) fn track<S>(id: usize, subscribe: S)
+where
+    S: FnOnce(Notify) -> ScopedConnection + 'static
+{
+    TRACKING.with(|tracking| {
+        if let Some(frame) = tracking.borrow_mut().last_mut() {
+            if !frame.iter().any(|dep| dep.id == id) {
+                frame.push(Dependency {
+                    id,
+                    subscribe: Box::new(subscribe)
+                });
+            }
+        }
+    });
+}
+
+// Pops the current tracking frame when dropped, even if the computation panics.
+struct TrackingFrame;
+
+impl TrackingFrame {
+    fn push() -> Self {
+        TRACKING.with(|tracking| tracking.borrow_mut().push(Vec::new()));
+        TrackingFrame
+    }
+
+    fn finish(self) -> Vec<Dependency> {
+        let deps = TRACKING.with(|tracking| tracking.borrow_mut().pop().unwrap_or_default());
+        std::mem::forget(self);
+        deps
+    }
+}
+
+impl Drop for TrackingFrame {
+    fn drop(&mut self) {
+        TRACKING.with(|tracking| tracking.borrow_mut().pop());
+    }
+}
+
+struct ComputedState<T> {
+    value: Option<T>,
+    dirty: bool,
+    dependencies: Vec<ScopedConnection>
+}
+
+struct ComputedInner<T>
+where
+    T: Clone + PartialEq + Send + Sync + 'static
+{
+    compute: Box<dyn Fn() -> T + Send + Sync>,
+    state: Mutex<ComputedState<T>>,
+    changed: Signal<(T, T)>
+}
+
+impl<T> ComputedInner<T>
+where
+    T: Clone + PartialEq + Send + Sync + 'static
+{
+    fn value(self: &Arc<Self>) -> T {
+        {
+            let state = self.state.lock().unwrap();
+            if let (false, Some(value)) = (state.dirty, &state.value) {
+                return value.clone();
+            }
+        }
+
+        self.evaluate()
+    }
+
+    // Runs the computation, records the values it reads, and subscribes to them.
+    fn evaluate(self: &Arc<Self>) -> T {
+        let frame = TrackingFrame::push();
+        let value = (self.compute)();
+        let deps = frame.finish();
+
+        let weak_inner = Arc::downgrade(self);
+        let notify: Notify = Arc::new(move || {
+            if let Some(inner) = weak_inner.upgrade() {
+                inner.invalidate();
+            }
+        });
+
+        let dependencies = deps.into_iter().map(|dep| (dep.subscribe)(notify.clone())).collect();
+
+        let mut state = self.state.lock().unwrap();
+        state.value = Some(value.clone());
+        state.dirty = false;
+        let old_dependencies = std::mem::replace(&mut state.dependencies, dependencies);
+        std::mem::drop(state);
+        std::mem::drop(old_dependencies);
+        value
+    }
+
+    // Called when one of the dependencies changes. Recomputation is deferred until the next read unless
+    // somebody is observing the change signal.
+    fn invalidate(self: &Arc<Self>) {
+        let mut state = self.state.lock().unwrap();
+        state.dirty = true;
+        if self.changed.count() == 0 {
+            return;
+        }
+
+        let old = state.value.clone();
+        std::mem::drop(state);
+
+        let new = self.evaluate();
+        if let Some(old) = old {
+            if old != new {
+                self.changed.emit(old, new);
+            }
+        }
+    }
+}
+
+/// A value derived from other observable values. A `Computed` is created from a closure that reads
+/// [Properties](crate::property::Property) and other `Computed`s. Every value read by the closure is recorded as a
+/// dependency, and the closure is run again whenever one of its dependencies changes. The dependencies are
+/// recorded again every time the closure runs, so values that are read conditionally are tracked correctly.
+///
+/// Recomputation is lazy: it is deferred until the next call to [get](Computed::get) unless a slot is connected
+/// to the [changed](Computed::changed) signal, in which case the value is recomputed immediately and the signal
+/// is emitted with the old and new values if the result differs from the previous one.
+///
+/// Internally a `Computed` subscribes to its dependencies with scoped connections, which are disconnected when
+/// the last handle to the `Computed` is dropped.
+/// # Example
+/// ```
+/// use signals2::*;
+/// use signals2::property::Property;
+/// use signals2::computed::Computed;
+///
+/// let width = Property::new(2);
+/// let height = Property::new(3);
+/// let area = {
+///     let (width, height) = (width.clone(), height.clone());
+///     Computed::new(move || width.get() * height.get())
+/// };
+///
+/// assert_eq!(area.get(), 6);
+/// area.changed().connect(|old, new| println!("{} -> {}", old, new));
+/// width.set(4); // prints "6 -> 12"
+/// height.set(3); // prints nothing, height did not change
+/// assert_eq!(area.get(), 12);
+/// ```
+pub struct Computed<T>
+where
+    T: Clone + PartialEq + Send + Sync + 'static
+{
+    inner: Arc<ComputedInner<T>>
+}
+
+impl<T> Clone for Computed<T>
+where
+    T: Clone + PartialEq + Send + Sync + 'static
+{
+    /// Creates another handle to the same computed value.
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone()
+        }
+    }
+}
+
+impl<T> Computed<T>
+where
+    T: Clone + PartialEq + Send + Sync + 'static
+{
+    /// Creates a new computed value. `compute` is not run until the value is first needed.
+    pub fn new<F>(compute: F) -> Self
+    where
+        F: Fn() -> T + Send + Sync + 'static
+    {
+        Computed {
+            inner: Arc::new(ComputedInner {
+                compute: Box::new(compute),
+                state: Mutex::new(ComputedState {
+                    value: None,
+                    dirty: true,
+                    dependencies: Vec::new()
+                }),
+                changed: Signal::new()
+            })
+        }
+    }
+
+    /// Returns the current value, recomputing it first if any of its dependencies have changed since
+    /// it was last computed.
+    pub fn get(&self) -> T {
+        let handle = self.inner.changed.get_connect_handle();
+        track(Arc::as_ptr(&self.inner) as *const () as usize, move |notify| {
+            handle.connect(move |_, _| notify()).scoped()
+        });
+
+        self.inner.value()
+    }
+
+    /// Returns a [ConnectHandle] for the signal that is emitted with the old and new values whenever the
+    /// computed value changes.
+    pub fn changed(&self) -> ConnectHandle<(T, T)> {
+        // the dependencies are unknown until the value has been computed at least once
+        self.inner.value();
+        self.inner.changed.get_connect_handle()
+    }
+
+    /// Returns the number of values this computed value currently depends on.
+    pub fn dependency_count(&self) -> usize {
+        self.inner.state.lock().unwrap().dependencies.len()
+    }
+}
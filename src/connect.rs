@@ -3,9 +3,17 @@
 // Distributed under the Boost Software License, Version 1.0. 
 // See http://www.boost.org/LICENSE_1_0.txt
 
-use std::sync::{Arc, Weak, atomic::{AtomicBool, AtomicUsize, Ordering}};
+use std::cmp;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::sync::{Arc, Weak, Mutex, atomic::{AtomicBool, AtomicUsize, Ordering}};
+use std::time::{Duration, Instant};
 
 use crate::{Signal, ConnectHandle};
+use crate::signal_core::next_id;
+use crate::untyped::UntypedSignal;
+use crate::transaction::SignalTransaction;
+use crate::context::EmissionContext;
 use crate::combiner::Combiner;
 
 /// Represents a position to connect a slot to in a group of slots.
@@ -36,11 +44,332 @@ where
     Back
 }
 
+/// Returned by a retaining slot to decide what happens to the slot after it has executed. See
+/// [connect_retaining](Connect1::connect_retaining).
+/// # Example
+/// ```
+/// use signals2::*;
+/// use std::sync::{Arc, atomic::{AtomicI32, Ordering}};
+///
+/// let sig: Signal<(i32,)> = Signal::new();
+/// let total = Arc::new(AtomicI32::new(0));
+/// let total_clone = total.clone();
+/// sig.connect_retaining(move |x| {
+///     total_clone.fetch_add(x, Ordering::SeqCst);
+///     if x < 0 { SlotControl::Disconnect } else { SlotControl::Keep }
+/// });
+///
+/// sig.emit(2);
+/// sig.emit(-1);
+/// sig.emit(5); // the slot has unregistered itself
+/// assert_eq!(total.load(Ordering::SeqCst), 1);
+/// assert_eq!(sig.count(), 0);
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SlotControl {
+    /// The slot stays connected.
+    Keep,
+    /// The slot is disconnected.
+    Disconnect,
+    /// The slot stays connected but is not executed by emissions that happen within the given duration.
+    Block(Duration)
+}
+
+// Adapts a slot function that returns a SlotControl into one that applies the SlotControl to the slot's connection.
+// The return type does not mention the slot's return type, so it does not require that type to be 'static.
+pub(crate) fn retaining<Args, F>(f: F) -> impl Fn(&Connection, Args) + Send + Sync + 'static
+where
+    F: Fn(Args) -> SlotControl + Send + Sync + 'static
+{
+    let blocked_until: Mutex<Option<Instant>> = Mutex::new(None);
+    move |conn, args| {
+        if !conn.connected() || blocked_until.lock().unwrap().map_or(false, |until| Instant::now() < until) {
+            return;
+        }
+
+        match f(args) {
+            SlotControl::Keep => {},
+            SlotControl::Disconnect => conn.disconnect(),
+            SlotControl::Block(duration) => *blocked_until.lock().unwrap() = Some(Instant::now() + duration)
+        }
+    }
+}
+
+/// Connect trait for signals with slots that accept a tuple of arguments of type `Args`. Unlike the arity specific
+/// traits ([Connect0] through [Connect12]), `Connect` is not limited in the number of arguments, and can be used to
+/// write code that is generic over signals of any signature. Slot functions connected through `Connect` accept their
+/// arguments as a single tuple.
+/// # Example
+/// ```
+/// use signals2::*;
+///
+/// fn log_all<S, Args>(sig: &S) -> Connection
+/// where
+///     S: Connect<Args, Return = ()>,
+///     Args: Clone + std::fmt::Debug + 'static
+/// {
+///     sig.connect_tuple(|args: Args| println!("{:?}", args))
+/// }
+///
+/// let sig: Signal<(i32, &'static str)> = Signal::new();
+/// log_all(&sig);
+/// sig.emit(1, "a"); // prints "(1, "a")"
+/// ```
+pub trait Connect<Args>
+where
+    Args: Clone + 'static
+{
+    /// The return type of the slot functions.
+    type Return;
+    /// The type used to order named groups of slots.
+    type Group: Ord + Send + Sync;
+
+    /// Connects the slot function `f` to the given [Group] at the given [Position].
+    fn connect_tuple_group_position<F>(&self, f: F, group: Group<Self::Group>, pos: Position) -> Connection
+    where
+        F: Fn(Args) -> Self::Return + Send + Sync + 'static;
+
+    /// Connects the extended slot function `f` to the given [Group] at the given [Position].
+    fn connect_tuple_group_position_extended<F>(&self, f: F, group: Group<Self::Group>, pos: Position) -> Connection
+    where
+        F: Fn(Connection, Args) -> Self::Return + Send + Sync + 'static;
+
+    /// Connects the contextual slot function `f` to the given [Group] at the given [Position]. The slot receives an
+    /// [EmissionContext] describing the emission that is executing it.
+    fn connect_tuple_group_position_with_context<F>(&self, f: F, group: Group<Self::Group>, pos: Position) -> Connection
+    where
+        F: Fn(&EmissionContext<Self::Group>, Args) -> Self::Return + Send + Sync + 'static;
+
+    /// Connects the partial slot function `f` to the given [Group] at the given [Position]. A partial slot receives
+    /// its own [Connection] and may decline to produce a value by returning `None`, in which case it does not
+    /// contribute to the return value of the emission.
+    fn connect_tuple_group_position_partial<F>(&self, f: F, group: Group<Self::Group>, pos: Position) -> Connection
+    where
+        F: Fn(&Connection, Args) -> Option<Self::Return> + Send + Sync + 'static;
+
+    /// Connects the retaining slot function `f` to the given [Group] at the given [Position]. The [SlotControl]
+    /// returned by `f` decides whether the slot stays connected. Retaining slots do not contribute to the return value
+    /// of an emission.
+    fn connect_tuple_group_position_retaining<F>(&self, f: F, group: Group<Self::Group>, pos: Position) -> Connection
+    where
+        F: Fn(Args) -> SlotControl + Send + Sync + 'static
+    {
+        // retaining slots never produce a value for the combiner
+        let f = retaining(f);
+        self.connect_tuple_group_position_partial(move |conn, args| { f(conn, args); None }, group, pos)
+    }
+
+    /// Connects the slot function `f` to the given [Group] at [Position::Back].
+    fn connect_tuple_group<F>(&self, f: F, group: Group<Self::Group>) -> Connection
+    where
+        F: Fn(Args) -> Self::Return + Send + Sync + 'static
+    {
+        self.connect_tuple_group_position(f, group, Position::Back)
+    }
+
+    /// Connects the slot function `f` to [Group::Back] at the given position.
+    fn connect_tuple_position<F>(&self, f: F, pos: Position) -> Connection
+    where
+        F: Fn(Args) -> Self::Return + Send + Sync + 'static
+    {
+        self.connect_tuple_group_position(f, Group::Back, pos)
+    }
+
+    /// Connects the slot function `f` to [Group::Back] at [Position::Back].
+    fn connect_tuple<F>(&self, f: F) -> Connection
+    where
+        F: Fn(Args) -> Self::Return + Send + Sync + 'static
+    {
+        self.connect_tuple_group_position(f, Group::Back, Position::Back)
+    }
+
+    /// Connects the extended slot function `f` to the given [Group] at [Position::Back].
+    fn connect_tuple_group_extended<F>(&self, f: F, group: Group<Self::Group>) -> Connection
+    where
+        F: Fn(Connection, Args) -> Self::Return + Send + Sync + 'static
+    {
+        self.connect_tuple_group_position_extended(f, group, Position::Back)
+    }
+
+    /// Connects the extended slot function `f` to [Group::Back] at the given position.
+    fn connect_tuple_position_extended<F>(&self, f: F, pos: Position) -> Connection
+    where
+        F: Fn(Connection, Args) -> Self::Return + Send + Sync + 'static
+    {
+        self.connect_tuple_group_position_extended(f, Group::Back, pos)
+    }
+
+    /// Connects the extended slot function `f` to [Group::Back] at [Position::Back].
+    fn connect_tuple_extended<F>(&self, f: F) -> Connection
+    where
+        F: Fn(Connection, Args) -> Self::Return + Send + Sync + 'static
+    {
+        self.connect_tuple_group_position_extended(f, Group::Back, Position::Back)
+    }
+
+    /// Connects the contextual slot function `f` to [Group::Back] at [Position::Back].
+    fn connect_tuple_with_context<F>(&self, f: F) -> Connection
+    where
+        F: Fn(&EmissionContext<Self::Group>, Args) -> Self::Return + Send + Sync + 'static
+    {
+        self.connect_tuple_group_position_with_context(f, Group::Back, Position::Back)
+    }
+
+    /// Connects the retaining slot function `f` to [Group::Back] at [Position::Back].
+    fn connect_tuple_retaining<F>(&self, f: F) -> Connection
+    where
+        F: Fn(Args) -> SlotControl + Send + Sync + 'static
+    {
+        self.connect_tuple_group_position_retaining(f, Group::Back, Position::Back)
+    }
+}
+
+impl<Args, R, C, G> Connect<Args> for Signal<Args, R, C, G>
+where
+    Args: Clone + 'static,
+    R: 'static,
+    C: Combiner<R> + 'static,
+    G: Ord + Send + Sync + 'static
+{
+    type Return = R;
+    type Group = G;
+
+    fn connect_tuple_group_position<F>(&self, f: F, group: Group<G>, pos: Position) -> Connection
+    where
+        F: Fn(Args) -> R + Send + Sync + 'static
+    {
+        let make_conn = self.make_conn();
+        self.modify_core(|core| core.connect(f, group, pos, make_conn))
+    }
+
+    fn connect_tuple_group_position_extended<F>(&self, f: F, group: Group<G>, pos: Position) -> Connection
+    where
+        F: Fn(Connection, Args) -> R + Send + Sync + 'static
+    {
+        let make_conn = self.make_conn();
+        self.modify_core(|core| core.connect_extended(f, group, pos, make_conn))
+    }
+
+    fn connect_tuple_group_position_with_context<F>(&self, f: F, group: Group<G>, pos: Position) -> Connection
+    where
+        F: Fn(&EmissionContext<G>, Args) -> R + Send + Sync + 'static
+    {
+        let make_conn = self.make_conn();
+        self.modify_core(|core| core.connect_with_context(f, group, pos, make_conn))
+    }
+
+    fn connect_tuple_group_position_partial<F>(&self, f: F, group: Group<G>, pos: Position) -> Connection
+    where
+        F: Fn(&Connection, Args) -> Option<R> + Send + Sync + 'static
+    {
+        self.connect_partial(f, group, pos)
+    }
+}
+
+impl<Args, R, C, G> Connect<Args> for ConnectHandle<Args, R, C, G>
+where
+    Args: Clone + 'static,
+    R: 'static,
+    C: Combiner<R> + 'static,
+    G: Ord + Send + Sync + 'static
+{
+    type Return = R;
+    type Group = G;
+
+    fn connect_tuple_group_position<F>(&self, f: F, group: Group<G>, pos: Position) -> Connection
+    where
+        F: Fn(Args) -> R + Send + Sync + 'static
+    {
+        self.weak_sig
+            .upgrade()
+            .map(|sig| sig.connect_tuple_group_position(f, group, pos))
+            .unwrap_or_else(Connection::empty)
+    }
+
+    fn connect_tuple_group_position_extended<F>(&self, f: F, group: Group<G>, pos: Position) -> Connection
+    where
+        F: Fn(Connection, Args) -> R + Send + Sync + 'static
+    {
+        self.weak_sig
+            .upgrade()
+            .map(|sig| sig.connect_tuple_group_position_extended(f, group, pos))
+            .unwrap_or_else(Connection::empty)
+    }
+
+    fn connect_tuple_group_position_with_context<F>(&self, f: F, group: Group<G>, pos: Position) -> Connection
+    where
+        F: Fn(&EmissionContext<G>, Args) -> R + Send + Sync + 'static
+    {
+        self.weak_sig
+            .upgrade()
+            .map(|sig| sig.connect_tuple_group_position_with_context(f, group, pos))
+            .unwrap_or_else(Connection::empty)
+    }
+
+    fn connect_tuple_group_position_partial<F>(&self, f: F, group: Group<G>, pos: Position) -> Connection
+    where
+        F: Fn(&Connection, Args) -> Option<R> + Send + Sync + 'static
+    {
+        self.weak_sig
+            .upgrade()
+            .map(|sig| sig.connect_tuple_group_position_partial(f, group, pos))
+            .unwrap_or_else(Connection::empty)
+    }
+}
+
+/// Implemented by functions that can be called with the elements of the tuple `Args` as individual arguments. Used by
+/// [connect_map](Connect1::connect_map) to call a slot function with the mapped arguments.
+pub trait TupleFn<Args, R> {
+    /// Calls the function with the elements of `args`.
+    fn call_tuple(&self, args: Args) -> R;
+}
+
+macro_rules! impl_tuple_fn {
+    ($($args:ident)*; $($params:ident)*) => {
+        impl<F, R, $($args,)*> TupleFn<($($args,)*), R> for F
+        where
+            F: Fn($($args,)*) -> R
+        {
+            fn call_tuple(&self, ($($params,)*): ($($args,)*)) -> R {
+                self($($params,)*)
+            }
+        }
+    };
+}
+
+impl_tuple_fn!(;);
+impl_tuple_fn!(T0; a);
+impl_tuple_fn!(T0 T1; a b);
+impl_tuple_fn!(T0 T1 T2; a b c);
+impl_tuple_fn!(T0 T1 T2 T3; a b c d);
+impl_tuple_fn!(T0 T1 T2 T3 T4; a b c d e);
+impl_tuple_fn!(T0 T1 T2 T3 T4 T5; a b c d e f);
+impl_tuple_fn!(T0 T1 T2 T3 T4 T5 T6; a b c d e f g);
+impl_tuple_fn!(T0 T1 T2 T3 T4 T5 T6 T7; a b c d e f g h);
+impl_tuple_fn!(T0 T1 T2 T3 T4 T5 T6 T7 T8; a b c d e f g h i);
+impl_tuple_fn!(T0 T1 T2 T3 T4 T5 T6 T7 T8 T9; a b c d e f g h i j);
+impl_tuple_fn!(T0 T1 T2 T3 T4 T5 T6 T7 T8 T9 T10; a b c d e f g h i j k);
+impl_tuple_fn!(T0 T1 T2 T3 T4 T5 T6 T7 T8 T9 T10 T11; a b c d e f g h i j k l);
+
 macro_rules! impl_connect {
+    // connect_first only exists for signals whose slots accept at least one argument
+    (@first; ) => {};
+    (@first $first:ident $($rest:ident)*; $first_param:ident $($rest_params:ident)*) => {
+        /// Connects the slot function `f`, which accepts only the first argument of the signal, to [Group::Back] at
+        /// [Position::Back]. The remaining arguments are ignored.
+        fn connect_first<F>(&self, f: F) -> Connection
+        where 
+            F: Fn($first) -> R + Send + Sync + 'static
+        {
+            self.connect_tuple(move |($first_param, ..)| f($first_param))
+        }
+    };
+
     ($name:ident; $($args:ident)*; $($params:ident)*) => {
 
-        /// Connect trait for signals with slots that accept the corresponding number of arguments. 
-        pub trait $name<R, C, G, $($args),*>
+        /// Connect trait for signals with slots that accept the corresponding number of arguments. A convenience layer
+        /// over [Connect] for slot functions that accept their arguments individually rather than as a tuple.
+        pub trait $name<R, C, G, $($args),*>: Connect<($($args,)*), Return = R, Group = G>
         where 
             ($($args,)*): Clone + 'static,
             R: 'static,
@@ -50,12 +379,37 @@ macro_rules! impl_connect {
             /// Connects the slot function `f` to the given [Group] at the given [Position]
             fn connect_group_position<F>(&self, f: F, group: Group<G>, pos: Position) -> Connection
             where 
-                F: Fn($($args,)*) -> R + Send + Sync + 'static;
+                F: Fn($($args,)*) -> R + Send + Sync + 'static
+            {
+                self.connect_tuple_group_position(move |($($params,)*)| f($($params,)*), group, pos)
+            }
 
             /// Connects the extended slot function `f` to the given [Group] at the given [Position]
             fn connect_group_position_extended<F>(&self, f: F, group: Group<G>, pos: Position) -> Connection
             where 
-                F: Fn(Connection, $($args,)*) -> R + Send + Sync + 'static;
+                F: Fn(Connection, $($args,)*) -> R + Send + Sync + 'static
+            {
+                self.connect_tuple_group_position_extended(move |conn, ($($params,)*)| f(conn, $($params,)*), group, pos)
+            }
+
+            /// Connects the contextual slot function `f` to the given [Group] at the given [Position]. The slot
+            /// receives an [EmissionContext] describing the emission that is executing it.
+            fn connect_group_position_with_context<F>(&self, f: F, group: Group<G>, pos: Position) -> Connection
+            where 
+                F: Fn(&EmissionContext<G>, $($args,)*) -> R + Send + Sync + 'static
+            {
+                self.connect_tuple_group_position_with_context(move |ctx, ($($params,)*)| f(ctx, $($params,)*), group, pos)
+            }
+
+            /// Connects the retaining slot function `f` to the given [Group] at the given [Position]. The
+            /// [SlotControl] returned by `f` decides whether the slot stays connected. Retaining slots do not
+            /// contribute to the return value of an emission.
+            fn connect_group_position_retaining<F>(&self, f: F, group: Group<G>, pos: Position) -> Connection
+            where 
+                F: Fn($($args,)*) -> SlotControl + Send + Sync + 'static
+            {
+                self.connect_tuple_group_position_retaining(move |($($params,)*)| f($($params,)*), group, pos)
+            }
 
             /// Connects the slot function `f` to the given [Group] at [Position::Back]. Equivalent to calling
             /// `connect_group_position(f, group, Position::Back)`.
@@ -110,102 +464,163 @@ macro_rules! impl_connect {
             {
                 self.connect_group_position_extended(f, Group::Back, Position::Back)
             }
-        }
 
-        impl<R, C, G, $($args,)*> $name<R, C, G, $($args,)*> for Signal<($($args,)*), R, C, G> 
-        where
-            ($($args,)*): Clone + 'static,
-            R: 'static,
-            C: Combiner<R> + 'static,
-            G: Ord + Send + Sync + 'static,
-        {
-            fn connect_group_position<F>(&self, f: F, group: Group<G>, pos: Position) -> Connection
-            where
-                F: Fn($($args,)*) -> R + Send + Sync + 'static
+            /// Connects the contextual slot function `f` to [Group::Back] at [Position::Back]. Equivalent to calling
+            /// `connect_group_position_with_context(f, Group::Back, Position::Back)`.
+            fn connect_with_context<F>(&self, f: F) -> Connection
+            where 
+                F: Fn(&EmissionContext<G>, $($args,)*) -> R + Send + Sync + 'static
             {
-                let weak_core = Arc::downgrade(&self.core);
+                self.connect_group_position_with_context(f, Group::Back, Position::Back)
+            }
 
-                let cleanup = move || {
-                    if let Some(core) = weak_core.upgrade() {
-                        let mut lock = core.write().unwrap();
-                        let mut core_clone = (**lock).clone();
-                        core_clone.cleanup();
-                        *lock = Arc::new(core_clone);
-                    }
-                };
+            /// Connects the retaining slot function `f` to [Group::Back] at [Position::Back]. Equivalent to calling
+            /// `connect_group_position_retaining(f, Group::Back, Position::Back)`.
+            fn connect_retaining<F>(&self, f: F) -> Connection
+            where 
+                F: Fn($($args,)*) -> SlotControl + Send + Sync + 'static
+            {
+                self.connect_group_position_retaining(f, Group::Back, Position::Back)
+            }
 
-                let make_conn = move |connected, blocker_count| {
-                    Connection::new(connected, blocker_count, Arc::new(cleanup))
-                };
+            /// Connects the slot function `f`, which accepts no arguments, to [Group::Back] at [Position::Back]. The
+            /// arguments of the signal are ignored.
+            fn connect_ignore_args<F>(&self, f: F) -> Connection
+            where 
+                F: Fn() -> R + Send + Sync + 'static
+            {
+                self.connect_tuple(move |_| f())
+            }
 
-                let mut lock = self.core.write().unwrap();
-                let mut core_clone = (**lock).clone();
+            impl_connect!(@first $($args)*; $($params)*);
 
-                let wrapped_f = move |($($params,)*)| f($($params,)*);
-                let conn = core_clone.connect(wrapped_f, group, pos, make_conn);
+            /// Connects the method `f` of `receiver` to [Group::Back] at [Position::Back]. The slot holds a strong
+            /// reference to `receiver`, keeping it alive for as long as the slot is connected.
+            /// # Example
+            /// ```
+            /// use signals2::*;
+            /// use std::sync::{Arc, atomic::{AtomicI32, Ordering}};
+            ///
+            /// struct Counter {
+            ///     total: AtomicI32
+            /// }
+            ///
+            /// impl Counter {
+            ///     fn add(&self, x: i32) {
+            ///         self.total.fetch_add(x, Ordering::SeqCst);
+            ///     }
+            /// }
+            ///
+            /// let sig: Signal<(i32,)> = Signal::new();
+            /// let counter = Arc::new(Counter { total: AtomicI32::new(0) });
+            /// sig.connect_method(&counter, Counter::add);
+            /// sig.emit(2);
+            /// sig.emit(3);
+            /// assert_eq!(counter.total.load(Ordering::SeqCst), 5);
+            /// ```
+            fn connect_method<T, F>(&self, receiver: &Arc<T>, f: F) -> Connection
+            where 
+                T: Send + Sync + 'static,
+                F: Fn(&T, $($args,)*) -> R + Send + Sync + 'static
+            {
+                let receiver = receiver.clone();
+                self.connect_tuple(move |($($params,)*)| f(&receiver, $($params,)*))
+            }
 
-                *lock = Arc::new(core_clone);
+            /// Connects the method `f` of `receiver` to [Group::Back] at [Position::Back]. The slot holds only a weak
+            /// reference to `receiver`, and the connection is tracked by the receiver's [Trackable], so the slot is
+            /// disconnected as soon as the last strong reference to `receiver` has been dropped.
+            /// # Example
+            /// ```
+            /// use signals2::*;
+            /// use std::sync::Arc;
+            ///
+            /// struct Doubler {
+            ///     trackable: Trackable
+            /// }
+            ///
+            /// impl Doubler {
+            ///     fn double(&self, x: i32) -> i32 {
+            ///         x * 2
+            ///     }
+            /// }
+            ///
+            /// impl AsRef<Trackable> for Doubler {
+            ///     fn as_ref(&self) -> &Trackable {
+            ///         &self.trackable
+            ///     }
+            /// }
+            ///
+            /// let sig: Signal<(i32,), i32> = Signal::new();
+            /// let doubler = Arc::new(Doubler { trackable: Trackable::new() });
+            /// let conn = sig.connect_weak_method(&doubler, Doubler::double);
+            /// assert_eq!(sig.emit(4), Some(8));
+            ///
+            /// std::mem::drop(doubler);
+            /// assert!(!conn.connected());
+            /// assert_eq!(sig.count(), 0);
+            /// ```
+            fn connect_weak_method<T, F>(&self, receiver: &Arc<T>, f: F) -> Connection
+            where 
+                T: AsRef<Trackable> + Send + Sync + 'static,
+                F: Fn(&T, $($args,)*) -> R + Send + Sync + 'static
+            {
+                let weak_receiver = Arc::downgrade(receiver);
+                // an emission may reach the slot while the receiver is being dropped, before its trackable is
+                let conn = self.connect_tuple_group_position_partial(move |_, ($($params,)*)| {
+                    weak_receiver.upgrade().map(|receiver| f(&receiver, $($params,)*))
+                }, Group::Back, Position::Back);
+                (**receiver).as_ref().track(conn.clone());
                 conn
             }
 
-            fn connect_group_position_extended<F>(&self, f: F, group: Group<G>, pos: Position) -> Connection
-            where
-                F: Fn(Connection, $($args,)*) -> R + Send + Sync + 'static
+            /// Connects the slot function `f` to [Group::Back] at [Position::Back]. The arguments of the signal are
+            /// passed to `map` as a tuple, and `f` is called with the elements of the tuple returned by `map`. Allows an
+            /// existing function to be used as a slot when its parameters do not match the signal's arguments.
+            /// # Example
+            /// ```
+            /// use signals2::*;
+            ///
+            /// fn describe(name: &'static str, count: usize) -> String {
+            ///     format!("{}: {}", name, count)
+            /// }
+            ///
+            /// let sig: Signal<(usize, bool, &'static str), String> = Signal::new();
+            /// sig.connect_map(|(count, _, name)| (name, count), describe);
+            /// assert_eq!(sig.emit(3, true, "apples"), Some("apples: 3".to_string()));
+            /// ```
+            fn connect_map<M, T, F>(&self, map: M, f: F) -> Connection
+            where 
+                M: Fn(($($args,)*)) -> T + Send + Sync + 'static,
+                F: TupleFn<T, R> + Send + Sync + 'static
             {
-                let weak_core = Arc::downgrade(&self.core);
-
-                let cleanup = move || {
-                    if let Some(core) = weak_core.upgrade() {
-                        let mut lock = core.write().unwrap();
-                        let mut core_clone = (**lock).clone();
-                        core_clone.cleanup();
-                        *lock = Arc::new(core_clone);
-                    }
-                };
-
-                let make_conn = move |connected, blocker_count| {
-                    Connection::new(connected, blocker_count, Arc::new(cleanup))
-                };
-
-                let mut lock = self.core.write().unwrap();
-                let mut core_clone = (**lock).clone();
-
-                let wrapped_f = move |conn, ($($params,)*)| f(conn, $($params,)*);
-                let conn = core_clone.connect_extended(wrapped_f, group, pos, make_conn);
-
-                *lock = Arc::new(core_clone);
-                conn
+                self.connect_tuple(move |args| f.call_tuple(map(args)))
             }
         }
 
-        // Implement Connect traits for COnnectHandle
+        impl<R, C, G, $($args,)*> $name<R, C, G, $($args,)*> for Signal<($($args,)*), R, C, G> 
+        where
+            ($($args,)*): Clone + 'static,
+            R: 'static,
+            C: Combiner<R> + 'static,
+            G: Ord + Send + Sync + 'static,
+        {}
+
         impl<R, C, G, $($args,)*> $name<R, C, G, $($args,)*> for ConnectHandle<($($args,)*), R, C, G> 
         where
             ($($args,)*): Clone + 'static,
             R: 'static,
             C: Combiner<R> + 'static,
             G: Ord + Send + Sync + 'static,
-        {
-            fn connect_group_position<F>(&self, f: F, group: Group<G>, pos: Position) -> Connection
-            where
-                F: Fn($($args,)*) -> R + Send + Sync + 'static
-            {
-                self.weak_sig
-                    .upgrade()
-                    .map(|sig| sig.connect_group_position(f, group, pos))
-                    .unwrap_or(Connection::empty())
-            }
+        {}
 
-            fn connect_group_position_extended<F>(&self, f: F, group: Group<G>, pos: Position) -> Connection
-            where
-                F: Fn(Connection, $($args,)*) -> R + Send + Sync + 'static
-            {
-                self.weak_sig
-                    .upgrade()
-                    .map(|sig| sig.connect_group_position_extended(f, group, pos))
-                    .unwrap_or(Connection::empty())
-            }
-        }
+        impl<R, C, G, $($args,)*> $name<R, C, G, $($args,)*> for SignalTransaction<($($args,)*), R, C, G> 
+        where
+            ($($args,)*): Clone + 'static,
+            R: 'static,
+            C: Combiner<R> + 'static,
+            G: Ord + Send + Sync + 'static,
+        {}
     };
 }
 
@@ -225,31 +640,71 @@ impl_connect!(Connect12; T0 T1 T2 T3 T4 T5 T6 T7 T8 T9 T10 T11; a b c d e f g h
 
 /// The implementation used by both [Connection] and [ScopedConnection].
 /// Takes a const bool parameter indicating whether it is a scoped connection or not.
+///
+/// Every connection is identified by the [id](ConnectionImpl::id) of its slot. Two connections compare equal if and
+/// only if they manage the same slot, which allows connections to be stored in a `HashSet` or a `BTreeSet`.
 #[derive(Clone)]
 pub struct ConnectionImpl<const SCOPED: bool>
 {
     weak_connected: Weak<AtomicBool>,
     weak_blocker_count: Weak<AtomicUsize>,
-    cleanup: Arc<dyn Fn() -> () + Send + Sync>
+    cleanup: Arc<dyn Fn() + Send + Sync>,
+    signal_id: u64,
+    slot_id: u64
 }
 
 impl<const SCOPED: bool> ConnectionImpl<SCOPED> {
-    fn new(weak_connected: Weak<AtomicBool>, weak_blocker_count: Weak<AtomicUsize>, cleanup: Arc<dyn Fn() -> () + Send + Sync>) -> Self {
+    pub(crate) fn new(weak_connected: Weak<AtomicBool>, weak_blocker_count: Weak<AtomicUsize>, cleanup: Arc<dyn Fn() + Send + Sync>, signal_id: u64, slot_id: u64) -> Self {
         Self {
             weak_connected,
             weak_blocker_count,
-            cleanup
+            cleanup,
+            signal_id,
+            slot_id
         }
     }
 
-    fn empty() -> Self {
+    // Creates a connection that is not associated with any signal. It still gets its own slot ID so that it
+    // never compares equal to any other connection.
+    pub(crate) fn empty() -> Self {
         Self {
             weak_connected: Weak::new(),
             weak_blocker_count: Weak::new(),
-            cleanup: Arc::new(|| ())
+            cleanup: Arc::new(|| ()),
+            signal_id: 0,
+            slot_id: next_id()
         }
     }
 
+    // The ID of the signal the slot was connected to, or zero if there is no such signal.
+    pub(crate) fn signal_id(&self) -> u64 {
+        self.signal_id
+    }
+
+    /// Returns the ID of the underlying slot. Slot IDs are unique and never change, even after the slot
+    /// is disconnected.
+    pub fn id(&self) -> u64 {
+        self.slot_id
+    }
+
+    /// Returns true if the underlying slot was connected to `sig` or to one of its clones.
+    /// # Example
+    /// ```
+    /// use signals2::*;
+    ///
+    /// let sig1: Signal<()> = Signal::new();
+    /// let sig2: Signal<()> = Signal::new();
+    /// let conn = sig1.connect(|| ());
+    /// assert!(conn.belongs_to(&sig1.clone()));
+    /// assert!(!conn.belongs_to(&sig2));
+    /// ```
+    pub fn belongs_to<S>(&self, sig: &S) -> bool
+    where
+        S: UntypedSignal + ?Sized
+    {
+        self.signal_id != 0 && self.signal_id == sig.id()
+    }
+
     /// Returns true if the underlying slot is still connected, false otherwise. Will return false 
     /// if the underlying signal no longer exists.
     pub fn connected(&self) -> bool {
@@ -307,7 +762,44 @@ impl ConnectionImpl<false> {
     /// Consumes the connection and returns a [ScopedConnection].
     #[must_use="ScopedConnection automatically disconnects when dropped"]
     pub fn scoped(self) -> ScopedConnection {
-        ScopedConnection::new(self.weak_connected.clone(), self.weak_blocker_count.clone(), self.cleanup.clone())
+        ScopedConnection::new(self.weak_connected.clone(), self.weak_blocker_count.clone(), self.cleanup.clone(), self.signal_id, self.slot_id)
+    }
+}
+
+impl<const SCOPED: bool> PartialEq for ConnectionImpl<SCOPED> {
+    fn eq(&self, other: &Self) -> bool {
+        self.slot_id == other.slot_id
+    }
+}
+
+impl<const SCOPED: bool> Eq for ConnectionImpl<SCOPED> {}
+
+impl<const SCOPED: bool> PartialOrd for ConnectionImpl<SCOPED> {
+    fn partial_cmp(&self, other: &Self) -> Option<cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<const SCOPED: bool> Ord for ConnectionImpl<SCOPED> {
+    /// Orders connections by the IDs of their slots, which are assigned in the order the slots were connected.
+    fn cmp(&self, other: &Self) -> cmp::Ordering {
+        self.slot_id.cmp(&other.slot_id)
+    }
+}
+
+impl<const SCOPED: bool> Hash for ConnectionImpl<SCOPED> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.slot_id.hash(state);
+    }
+}
+
+impl<const SCOPED: bool> fmt::Debug for ConnectionImpl<SCOPED> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct(if SCOPED { "ScopedConnection" } else { "Connection" })
+            .field("id", &self.slot_id)
+            .field("signal_id", &self.signal_id)
+            .field("connected", &self.connected())
+            .finish()
     }
 }
 
@@ -395,7 +887,7 @@ pub struct SharedConnectionBlock {
 }
 
 impl SharedConnectionBlock {
-    fn new(weak_blocker_count: Weak<AtomicUsize>, initially_blocking: bool) -> Self {
+    pub(crate) fn new(weak_blocker_count: Weak<AtomicUsize>, initially_blocking: bool) -> Self {
         let shared_block = Self {
             weak_blocker_count,
             blocking: AtomicBool::new(false)
@@ -456,4 +948,160 @@ impl Drop for SharedConnectionBlock {
     fn drop(&mut self) {
         self.unblock();
     }
-}
\ No newline at end of file
+}
+
+/// A collection of [ScopedConnections](ScopedConnection). Every connection added to a `ConnectionSet` is disconnected
+/// when the set is dropped, which makes it possible to manage the lifetime of many slots, possibly connected to many
+/// different signals, with a single object.
+/// # Example
+/// ```
+/// use signals2::*;
+///
+/// let sig1: Signal<(), i32> = Signal::new();
+/// let sig2: Signal<(i32,)> = Signal::new();
+/// let connections = ConnectionSet::new();
+/// connections.add(sig1.connect(|| 1));
+/// connections.add(sig2.connect(|x| println!("{}", x)));
+/// assert_eq!(connections.len(), 2);
+///
+/// {
+///     let _blocker = connections.block_all();
+///     assert_eq!(sig1.emit(), None);
+/// }
+///
+/// assert_eq!(sig1.emit(), Some(1));
+/// std::mem::drop(connections);
+/// assert_eq!(sig1.count(), 0);
+/// assert_eq!(sig2.count(), 0);
+/// ```
+#[derive(Default)]
+pub struct ConnectionSet {
+    connections: Mutex<Vec<ScopedConnection>>
+}
+
+impl ConnectionSet {
+    /// Creates an empty `ConnectionSet`.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a connection to the set. The connection will be disconnected when the set is dropped.
+    pub fn add(&self, conn: Connection) {
+        let mut connections = self.connections.lock().unwrap();
+        connections.retain(|conn| conn.connected());
+        connections.push(conn.scoped());
+    }
+
+    /// Disconnects every connection in the set and removes them from the set.
+    pub fn disconnect_all(&self) {
+        let connections = std::mem::take(&mut *self.connections.lock().unwrap());
+        std::mem::drop(connections);
+    }
+
+    /// Blocks every connection currently in the set until the returned [ConnectionSetBlock] is dropped or unblocked.
+    /// Connections that are added to the set afterwards are not blocked.
+    #[must_use="connection set blocks are automatically unblocked when dropped"]
+    pub fn block_all(&self) -> ConnectionSetBlock {
+        ConnectionSetBlock {
+            blocks: self.connections
+                .lock()
+                .unwrap()
+                .iter()
+                .map(|conn| conn.shared_block(true))
+                .collect()
+        }
+    }
+
+    /// Returns the number of connections in the set that are still connected.
+    pub fn len(&self) -> usize {
+        self.connections.lock().unwrap().iter().filter(|conn| conn.connected()).count()
+    }
+
+    /// Returns true if none of the connections in the set are still connected.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+/// Blocks every connection that was in a [ConnectionSet] when [block_all](ConnectionSet::block_all) was called.
+/// Behaves like a [SharedConnectionBlock] for each of those connections, and unblocks them when dropped.
+pub struct ConnectionSetBlock {
+    blocks: Vec<SharedConnectionBlock>
+}
+
+impl ConnectionSetBlock {
+    /// Causes the `ConnectionSetBlock` to begin blocking, if it isn't already.
+    pub fn block(&self) {
+        self.blocks.iter().for_each(SharedConnectionBlock::block);
+    }
+
+    /// Causes the `ConnectionSetBlock` to stop blocking, if it isn't already.
+    pub fn unblock(&self) {
+        self.blocks.iter().for_each(SharedConnectionBlock::unblock);
+    }
+
+    /// Returns true if the `ConnectionSetBlock` is currently blocking, false otherwise.
+    pub fn blocking(&self) -> bool {
+        self.blocks.iter().any(SharedConnectionBlock::blocking)
+    }
+}
+
+/// A helper that can be embedded in a struct so that slots connected on behalf of the struct are disconnected when
+/// it is dropped, similar to `boost::signals2::trackable`. Cloning a `Trackable` results in a new, empty `Trackable`,
+/// so the connections of one object are never shared with another.
+/// # Example
+/// ```
+/// use signals2::*;
+///
+/// struct Listener {
+///     trackable: Trackable
+/// }
+///
+/// impl Listener {
+///     fn listen(&self, sig: &Signal<(i32,)>) {
+///         self.trackable.track(sig.connect(|x| println!("received {}", x)));
+///     }
+/// }
+///
+/// let sig: Signal<(i32,)> = Signal::new();
+/// let listener = Listener { trackable: Trackable::new() };
+/// listener.listen(&sig);
+/// sig.emit(1); // prints "received 1"
+///
+/// std::mem::drop(listener);
+/// sig.emit(2); // prints nothing
+/// assert_eq!(sig.count(), 0);
+/// ```
+#[derive(Default)]
+pub struct Trackable {
+    connections: ConnectionSet
+}
+
+impl Trackable {
+    /// Creates a `Trackable` that is not tracking any connections.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Tracks `conn`, which will be disconnected when the `Trackable` is dropped.
+    pub fn track(&self, conn: Connection) {
+        self.connections.add(conn);
+    }
+
+    /// Disconnects every tracked connection.
+    pub fn disconnect_all(&self) {
+        self.connections.disconnect_all();
+    }
+
+    /// Returns the number of tracked connections that are still connected.
+    pub fn tracked_count(&self) -> usize {
+        self.connections.len()
+    }
+}
+
+impl Clone for Trackable {
+    /// Creates a new `Trackable` that is not tracking any connections.
+    fn clone(&self) -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,130 @@
+// Copyright Christian Daley 2021
+// Copyright Frank Mori Hess 2007-2008.
+// Distributed under the Boost Software License, Version 1.0. 
+// See http://www.boost.org/LICENSE_1_0.txt
+
+use std::sync::{Mutex, atomic::{AtomicBool, Ordering}};
+
+use crate::{Connection, Group};
+
+// The state shared by every slot executed during a single emission.
+pub(crate) struct Emission {
+    seq: u64,
+    depth: usize,
+    stopped: AtomicBool,
+    deferred: Mutex<Vec<Connection>>
+}
+
+impl Emission {
+    pub fn new(seq: u64, depth: usize) -> Self {
+        Emission {
+            seq,
+            depth,
+            stopped: AtomicBool::new(false),
+            deferred: Mutex::new(Vec::new())
+        }
+    }
+
+    pub fn depth(&self) -> usize {
+        self.depth
+    }
+
+    pub fn stopped(&self) -> bool {
+        self.stopped.load(Ordering::SeqCst)
+    }
+
+    // Disconnects the slots that asked to be disconnected once the emission completed.
+    pub fn finish(&self) {
+        let deferred = std::mem::take(&mut *self.deferred.lock().unwrap());
+        for conn in deferred {
+            conn.disconnect();
+        }
+    }
+}
+
+/// Information about the emission that is executing a slot, passed to slots connected with
+/// [connect_with_context](crate::Connect2::connect_with_context) and its variants. The context also allows the slot
+/// to stop the emission from executing any further slots, and to disconnect itself once the emission completes.
+/// # Example
+/// ```
+/// use signals2::*;
+///
+/// let sig: Signal<(i32,), i32, combiner::VecCombiner> = Signal::new();
+/// sig.connect_with_context(|ctx, x| {
+///     if x < 0 {
+///         ctx.stop_propagation();
+///     }
+///     x
+/// });
+/// sig.connect(|x| x * 10);
+///
+/// assert_eq!(sig.emit(1), vec!(1, 10));
+/// assert_eq!(sig.emit(-1), vec!(-1));
+/// ```
+pub struct EmissionContext<'a, G>
+where
+    G: Ord + Send + Sync
+{
+    emission: &'a Emission,
+    group: &'a Group<G>,
+    index: usize,
+    conn: &'a Connection
+}
+
+impl<'a, G> EmissionContext<'a, G>
+where
+    G: Ord + Send + Sync
+{
+    pub(crate) fn new(emission: &'a Emission, group: &'a Group<G>, index: usize, conn: &'a Connection) -> Self {
+        EmissionContext {
+            emission,
+            group,
+            index,
+            conn
+        }
+    }
+
+    /// Returns the sequence number of the emission. Every emission of a signal that executes its slots receives the
+    /// next sequence number, starting at 0. Emissions made while the signal had no slots connected with context, the
+    /// [Allow](crate::reentrancy::ReentrancyPolicy::Allow) policy and no maximum depth are not numbered.
+    pub fn sequence(&self) -> u64 {
+        self.emission.seq
+    }
+
+    /// Returns the number of emissions of the signal that were in progress on the emitting thread when the slot was
+    /// executed, including this one. The depth of an emission that is not nested inside another is 1.
+    pub fn depth(&self) -> usize {
+        self.emission.depth
+    }
+
+    /// Returns the [Group] the slot is connected to.
+    pub fn group(&self) -> &Group<G> {
+        self.group
+    }
+
+    /// Returns the position of the slot among the slots executed by the emission, starting at 0.
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
+    /// Returns the [Connection] that manages the slot.
+    pub fn connection(&self) -> &Connection {
+        self.conn
+    }
+
+    /// Prevents the emission from executing any slots after this one. Slots of a parallel emission that have already
+    /// started are not affected.
+    pub fn stop_propagation(&self) {
+        self.emission.stopped.store(true, Ordering::SeqCst);
+    }
+
+    /// Returns `true` if a slot has stopped the propagation of the emission.
+    pub fn propagation_stopped(&self) -> bool {
+        self.emission.stopped()
+    }
+
+    /// Disconnects the slot once the emission has completed and the combiner has produced its output.
+    pub fn disconnect_after_emission(&self) {
+        self.emission.deferred.lock().unwrap().push(self.conn.clone());
+    }
+}
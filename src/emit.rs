@@ -3,57 +3,184 @@
 // Distributed under the Boost Software License, Version 1.0. 
 // See http://www.boost.org/LICENSE_1_0.txt
 
-use std::mem;
-
 use crate::{Signal, EmitHandle};
 use crate::combiner::Combiner;
 
+/// Emit trait for signals with slots that accept a tuple of arguments of type `Args`. Unlike the arity specific
+/// traits ([Emit0] through [Emit12]), `Emit` is not limited in the number of arguments, and can be used to write code
+/// that is generic over signals of any signature.
+/// # Example
+/// ```
+/// use signals2::*;
+///
+/// fn emit_twice<S: Emit<Args>, Args: Clone>(sig: &S, args: Args) -> S::Output {
+///     sig.emit_tuple(args.clone());
+///     sig.emit_tuple(args)
+/// }
+///
+/// let sig: Signal<(i32, i32), i32> = Signal::new();
+/// sig.connect(|x, y| x + y);
+/// assert_eq!(emit_twice(&sig, (1, 2)), Some(3));
+/// ```
+pub trait Emit<Args>
+where
+    Args: Clone
+{
+    /// The return type of the slots.
+    type Return;
+    /// The return value of `emit_tuple` will be `C::Output` for [Signals](Signal) and `Option<C::Output>` for
+    /// [EmitHandles](EmitHandle), where `C` is the combiner type of the signal.
+    type Output;
+
+    /// Executes the signal's underlying slots, passing clones of the given tuple of arguments to the slot functions.
+    fn emit_tuple(&self, args: Args) -> Self::Output;
+
+    /// Executes all of the signal's eligible slots concurrently. See [Emit1::emit_parallel] for details. The default
+    /// implementation executes the slots sequentially with `emit_tuple`.
+    fn emit_tuple_parallel(&self, args: Args) -> Self::Output
+    where
+        Args: Send + Sync,
+        Self::Return: Send
+    {
+        self.emit_tuple(args)
+    }
+
+    /// Executes all of the signal's eligible slots concurrently, one group at a time.
+    /// See [Emit1::emit_parallel_grouped] for details. The default implementation executes the slots sequentially with
+    /// `emit_tuple`.
+    fn emit_tuple_parallel_grouped(&self, args: Args) -> Self::Output
+    where
+        Args: Send + Sync,
+        Self::Return: Send
+    {
+        self.emit_tuple(args)
+    }
+}
+
+impl<Args, R, C, G> Emit<Args> for Signal<Args, R, C, G>
+where
+    Args: Clone + 'static,
+    R: 'static,
+    C: Combiner<R> + 'static,
+    G: Ord + Send + Sync + 'static
+{
+    type Return = R;
+    type Output = C::Output;
+
+    fn emit_tuple(&self, args: Args) -> C::Output {
+        self.emit_args(&args)
+    }
+
+    fn emit_tuple_parallel(&self, args: Args) -> C::Output
+    where
+        Args: Send + Sync,
+        R: Send
+    {
+        self.snapshot().emit_parallel(&args, false)
+    }
+
+    fn emit_tuple_parallel_grouped(&self, args: Args) -> C::Output
+    where
+        Args: Send + Sync,
+        R: Send
+    {
+        self.snapshot().emit_parallel(&args, true)
+    }
+}
+
+impl<Args, R, C, G> Emit<Args> for EmitHandle<Args, R, C, G>
+where
+    Args: Clone + 'static,
+    R: 'static,
+    C: Combiner<R> + 'static,
+    G: Ord + Send + Sync + 'static
+{
+    type Return = R;
+    type Output = Option<C::Output>;
+
+    fn emit_tuple(&self, args: Args) -> Option<C::Output> {
+        self.weak_sig
+            .upgrade()
+            .map(|sig| sig.emit_tuple(args))
+    }
+
+    fn emit_tuple_parallel(&self, args: Args) -> Option<C::Output>
+    where
+        Args: Send + Sync,
+        R: Send
+    {
+        self.weak_sig
+            .upgrade()
+            .map(|sig| sig.emit_tuple_parallel(args))
+    }
+
+    fn emit_tuple_parallel_grouped(&self, args: Args) -> Option<C::Output>
+    where
+        Args: Send + Sync,
+        R: Send
+    {
+        self.weak_sig
+            .upgrade()
+            .map(|sig| sig.emit_tuple_parallel_grouped(args))
+    }
+}
+
 macro_rules! impl_emit {
     ($name:ident; $($args:ident)*; $($params:ident)*) => {
 
-        /// Emit trait for signals with slots that accept the corresponding number of arguments. 
-        pub trait $name<R, C, $($args,)*> 
-        where 
+        /// Emit trait for signals with slots that accept the corresponding number of arguments. A convenience layer over
+        /// [Emit] that accepts the arguments individually rather than as a tuple.
+        #[allow(clippy::too_many_arguments)]
+        pub trait $name<R, C, $($args,)*>: Emit<($($args,)*), Return = R>
+        where
             ($($args,)*): Clone,
             C: Combiner<R> + 'static
         {
-            /// The return value of `emit` will be `C::Output` for [Signals](Signal) and `Option<C::Output>` for [EmitHandles](EmitHandle)
-            type Output;
             /// Executes the signal's underlying slots, passing clones of the given arguments to the slot
-            /// functions. 
-            fn emit(&self, $($params: $args,)*) -> Self::Output;
-        }
+            /// functions.
+            fn emit(&self, $($params: $args,)*) -> Self::Output {
+                self.emit_tuple(($($params,)*))
+            }
 
-        impl<R, C, G, $($args,)*> $name<R, C, $($args,)*> for Signal<($($args,)*), R, C, G> 
-        where 
-            ($($args,)*): Clone,
-            C: Combiner<R> + 'static,
-            G: Ord + Send + Sync
-        {
-            type Output = C::Output;
+            /// Executes all of the signal's eligible slots concurrently, passing clones of the given arguments to the
+            /// slot functions. Every slot is executed before the combiner is invoked, and the slots' return values are handed
+            /// to the combiner in group/position order, so the output is the same as it would be for `emit`. With the `rayon`
+            /// feature enabled the slots run on rayon's global thread pool, otherwise they are divided among a bounded
+            /// number of scoped threads. If a slot panics, the panic is resumed once every other slot has finished.
+            fn emit_parallel(&self, $($params: $args,)*) -> Self::Output
+            where
+                ($($args,)*): Send + Sync,
+                R: Send
+            {
+                self.emit_tuple_parallel(($($params,)*))
+            }
 
-            fn emit(&self, $($params: $args,)*) -> C::Output {
-                let lock = self.core.read().unwrap();
-                let handle = lock.clone();
-                mem::drop(lock);
-                handle.emit(&($($params,)*))
+            /// Like `emit_parallel`, except that each [Group](crate::Group) acts as a barrier. All of the slots in one
+            /// group run concurrently and finish before any of the slots in the next group start.
+            fn emit_parallel_grouped(&self, $($params: $args,)*) -> Self::Output
+            where
+                ($($args,)*): Send + Sync,
+                R: Send
+            {
+                self.emit_tuple_parallel_grouped(($($params,)*))
             }
         }
 
-        impl<R, C, G, $($args,)*> $name<R, C, $($args,)*> for EmitHandle<($($args,)*), R, C, G> 
-        where 
-            ($($args,)*): Clone,
+        impl<R, C, G, $($args,)*> $name<R, C, $($args,)*> for Signal<($($args,)*), R, C, G>
+        where
+            ($($args,)*): Clone + 'static,
+            R: 'static,
             C: Combiner<R> + 'static,
-            G: Ord + Send + Sync
-        {
-            type Output = Option<C::Output>;
+            G: Ord + Send + Sync + 'static
+        {}
 
-            fn emit(&self, $($params: $args,)*) -> Option<C::Output> {
-                self.weak_sig
-                    .upgrade()
-                    .map(|sig| sig.emit($($params,)*))
-            }
-        }
+        impl<R, C, G, $($args,)*> $name<R, C, $($args,)*> for EmitHandle<($($args,)*), R, C, G>
+        where
+            ($($args,)*): Clone + 'static,
+            R: 'static,
+            C: Combiner<R> + 'static,
+            G: Ord + Send + Sync + 'static
+        {}
     };
 }
 
@@ -69,4 +196,4 @@ impl_emit!(Emit8; T0 T1 T2 T3 T4 T5 T6 T7; a b c d e f g h);
 impl_emit!(Emit9; T0 T1 T2 T3 T4 T5 T6 T7 T8; a b c d e f g h i);
 impl_emit!(Emit10; T0 T1 T2 T3 T4 T5 T6 T7 T8 T9; a b c d e f g h i j);
 impl_emit!(Emit11; T0 T1 T2 T3 T4 T5 T6 T7 T8 T9 T10; a b c d e f g h i j k);
-impl_emit!(Emit12; T0 T1 T2 T3 T4 T5 T6 T7 T8 T9 T10 T11; a b c d e f g h i j k l);
\ No newline at end of file
+impl_emit!(Emit12; T0 T1 T2 T3 T4 T5 T6 T7 T8 T9 T10 T11; a b c d e f g h i j k l);
@@ -0,0 +1,305 @@
+// Copyright Christian Daley 2021
+// Copyright Frank Mori Hess 2007-2008.
+// Distributed under the Boost Software License, Version 1.0. 
+// See http://www.boost.org/LICENSE_1_0.txt
+
+use std::any::{Any, TypeId};
+use std::collections::HashMap;
+use std::sync::{Arc, Weak, RwLock};
+
+use crate::{Signal, Connection, Group, Position};
+use crate::combiner::DefaultCombiner;
+use crate::connect::Connect1;
+use crate::emit::Emit1;
+
+type EventSignal<E, G> = Signal<(E,), (), DefaultCombiner, G>;
+
+struct BusInner<G>
+where
+    G: Ord + Send + Sync + 'static
+{
+    // Maps the TypeId of every event type to an EventSignal<E, G>.
+    signals: RwLock<HashMap<TypeId, Box<dyn Any + Send + Sync>>>,
+    _group: std::marker::PhantomData<fn() -> G>
+}
+
+impl<G> BusInner<G>
+where
+    G: Ord + Send + Sync + 'static
+{
+    fn get<E>(&self) -> Option<EventSignal<E, G>>
+    where
+        E: Clone + Send + Sync + 'static
+    {
+        self.signals
+            .read()
+            .unwrap()
+            .get(&TypeId::of::<E>())
+            .and_then(|sig| sig.downcast_ref::<EventSignal<E, G>>())
+            .cloned()
+    }
+
+    fn get_or_insert<E>(&self) -> EventSignal<E, G>
+    where
+        E: Clone + Send + Sync + 'static
+    {
+        if let Some(sig) = self.get::<E>() {
+            return sig;
+        }
+
+        self.signals
+            .write()
+            .unwrap()
+            .entry(TypeId::of::<E>())
+            .or_insert_with(|| Box::new(EventSignal::<E, G>::new()))
+            .downcast_ref::<EventSignal<E, G>>()
+            .cloned()
+            .unwrap()
+    }
+
+    fn subscribe<E, F>(&self, f: F, group: Group<G>, pos: Position) -> Connection
+    where
+        E: Clone + Send + Sync + 'static,
+        F: Fn(E) + Send + Sync + 'static
+    {
+        self.get_or_insert::<E>().connect_group_position(f, group, pos)
+    }
+
+    fn publish<E>(&self, event: E)
+    where
+        E: Clone + Send + Sync + 'static
+    {
+        if let Some(sig) = self.get::<E>() {
+            sig.emit(event);
+        }
+    }
+}
+
+/// A bus that delivers events to subscribers based on the type of the event. Internally the bus holds one signal
+/// per event type, which allows modules to communicate without sharing concrete [Signal] instances.
+/// Subscribers are executed in [Group] and [Position] order, just like the slots of a signal. Events are
+/// delivered in a fire-and-forget manner; subscribers do not return anything.
+///
+/// An `EventBus` is a handle to its subscriptions. Cloning a bus results in two handles to the same subscriptions.
+/// [PublishHandles](PublishHandle) and [SubscribeHandles](SubscribeHandle) can be used to limit what a module can do
+/// with the bus.
+/// # Example
+/// ```
+/// use signals2::event_bus::EventBus;
+///
+/// #[derive(Clone)]
+/// struct Saved {
+///     path: String
+/// }
+///
+/// let bus: EventBus = EventBus::new();
+/// bus.subscribe(|event: Saved| println!("saved {}", event.path));
+/// bus.publish(Saved { path: "a.txt".to_string() }); // prints "saved a.txt"
+/// bus.publish(5); // nobody is subscribed to i32 events, nothing happens
+/// ```
+pub struct EventBus<G = i32>
+where
+    G: Ord + Send + Sync + 'static
+{
+    inner: Arc<BusInner<G>>
+}
+
+impl<G> Clone for EventBus<G>
+where
+    G: Ord + Send + Sync + 'static
+{
+    /// Creates another handle to the same bus.
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone()
+        }
+    }
+}
+
+impl<G> Default for EventBus<G>
+where
+    G: Ord + Send + Sync + 'static
+{
+    /// Creates a bus with no subscribers.
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<G> EventBus<G>
+where
+    G: Ord + Send + Sync + 'static
+{
+    /// Creates a bus with no subscribers.
+    pub fn new() -> Self {
+        EventBus {
+            inner: Arc::new(BusInner {
+                signals: RwLock::new(HashMap::new()),
+                _group: std::marker::PhantomData
+            })
+        }
+    }
+
+    /// Subscribes `f` to events of type `E` in the given [Group] at the given [Position].
+    pub fn subscribe_group_position<E, F>(&self, f: F, group: Group<G>, pos: Position) -> Connection
+    where
+        E: Clone + Send + Sync + 'static,
+        F: Fn(E) + Send + Sync + 'static
+    {
+        self.inner.subscribe(f, group, pos)
+    }
+
+    /// Subscribes `f` to events of type `E` in the given [Group] at [Position::Back].
+    pub fn subscribe_group<E, F>(&self, f: F, group: Group<G>) -> Connection
+    where
+        E: Clone + Send + Sync + 'static,
+        F: Fn(E) + Send + Sync + 'static
+    {
+        self.inner.subscribe(f, group, Position::Back)
+    }
+
+    /// Subscribes `f` to events of type `E` in [Group::Back] at [Position::Back].
+    pub fn subscribe<E, F>(&self, f: F) -> Connection
+    where
+        E: Clone + Send + Sync + 'static,
+        F: Fn(E) + Send + Sync + 'static
+    {
+        self.inner.subscribe(f, Group::Back, Position::Back)
+    }
+
+    /// Delivers `event` to every subscriber of events of type `E`.
+    pub fn publish<E>(&self, event: E)
+    where
+        E: Clone + Send + Sync + 'static
+    {
+        self.inner.publish(event);
+    }
+
+    /// Returns the number of subscribers for events of type `E`.
+    pub fn subscriber_count<E>(&self) -> usize
+    where
+        E: Clone + Send + Sync + 'static
+    {
+        self.inner.get::<E>().map_or(0, |sig| sig.count())
+    }
+
+    /// Creates a [PublishHandle] that can be used to publish events to the bus.
+    pub fn get_publish_handle(&self) -> PublishHandle<G> {
+        PublishHandle {
+            weak_inner: Arc::downgrade(&self.inner)
+        }
+    }
+
+    /// Creates a [SubscribeHandle] that can be used to subscribe to events on the bus.
+    pub fn get_subscribe_handle(&self) -> SubscribeHandle<G> {
+        SubscribeHandle {
+            weak_inner: Arc::downgrade(&self.inner)
+        }
+    }
+}
+
+/// A handle to an [EventBus] that only allows events to be published. Internally a `PublishHandle` holds a weak
+/// reference to the bus. If the bus no longer exists, published events are discarded.
+pub struct PublishHandle<G = i32>
+where
+    G: Ord + Send + Sync + 'static
+{
+    weak_inner: Weak<BusInner<G>>
+}
+
+impl<G> Clone for PublishHandle<G>
+where
+    G: Ord + Send + Sync + 'static
+{
+    fn clone(&self) -> Self {
+        Self {
+            weak_inner: self.weak_inner.clone()
+        }
+    }
+}
+
+impl<G> PublishHandle<G>
+where
+    G: Ord + Send + Sync + 'static
+{
+    /// Delivers `event` to every subscriber of events of type `E`. Returns false if the bus no longer exists.
+    pub fn publish<E>(&self, event: E) -> bool
+    where
+        E: Clone + Send + Sync + 'static
+    {
+        self.weak_inner
+            .upgrade()
+            .map(|inner| inner.publish(event))
+            .is_some()
+    }
+}
+
+/// A handle to an [EventBus] that only allows subscribing to events. Internally a `SubscribeHandle` holds a weak
+/// reference to the bus. If the bus no longer exists, the returned connections are in a disconnected state.
+/// # Example
+/// ```
+/// use signals2::event_bus::EventBus;
+///
+/// let bus: EventBus = EventBus::new();
+/// let subscribe_handle = bus.get_subscribe_handle();
+/// let publish_handle = bus.get_publish_handle();
+///
+/// let conn = subscribe_handle.subscribe(|x: i32| println!("{}", x));
+/// assert!(publish_handle.publish(1)); // prints "1"
+///
+/// std::mem::drop(bus);
+/// assert!(!conn.connected());
+/// assert!(!publish_handle.publish(2));
+/// ```
+pub struct SubscribeHandle<G = i32>
+where
+    G: Ord + Send + Sync + 'static
+{
+    weak_inner: Weak<BusInner<G>>
+}
+
+impl<G> Clone for SubscribeHandle<G>
+where
+    G: Ord + Send + Sync + 'static
+{
+    fn clone(&self) -> Self {
+        Self {
+            weak_inner: self.weak_inner.clone()
+        }
+    }
+}
+
+impl<G> SubscribeHandle<G>
+where
+    G: Ord + Send + Sync + 'static
+{
+    /// Subscribes `f` to events of type `E` in the given [Group] at the given [Position].
+    pub fn subscribe_group_position<E, F>(&self, f: F, group: Group<G>, pos: Position) -> Connection
+    where
+        E: Clone + Send + Sync + 'static,
+        F: Fn(E) + Send + Sync + 'static
+    {
+        self.weak_inner
+            .upgrade()
+            .map(|inner| inner.subscribe(f, group, pos))
+            .unwrap_or_else(Connection::empty)
+    }
+
+    /// Subscribes `f` to events of type `E` in the given [Group] at [Position::Back].
+    pub fn subscribe_group<E, F>(&self, f: F, group: Group<G>) -> Connection
+    where
+        E: Clone + Send + Sync + 'static,
+        F: Fn(E) + Send + Sync + 'static
+    {
+        self.subscribe_group_position(f, group, Position::Back)
+    }
+
+    /// Subscribes `f` to events of type `E` in [Group::Back] at [Position::Back].
+    pub fn subscribe<E, F>(&self, f: F) -> Connection
+    where
+        E: Clone + Send + Sync + 'static,
+        F: Fn(E) + Send + Sync + 'static
+    {
+        self.subscribe_group_position(f, Group::Back, Position::Back)
+    }
+}
@@ -10,20 +10,80 @@
 //! "emitted". Signals and their corresponding slots can be managed through the use of [connections](Connection)
 //! and [shared connection blocks](SharedConnectionBlock).
 //!
-//! `signals2` contains no unsafe code and compiles on stable Rust 1.53. 
+//! `signals2` contains no unsafe code and compiles on stable Rust 1.63. 
 //! 
 //! `signals2` is distributed under the [Boost Software License, Version 1.0](https://www.boost.org/LICENSE_1_0.txt).
 //!
+//! ### Optional features
+//! * `rayon` - Runs the slots of [emit_parallel](Emit0::emit_parallel) on rayon's global thread pool instead of
+//!   dividing them among scoped threads.
+//! * `futures` - Enables [Signal::stream] and implements `Sink` for [EmitHandle] (see the [stream] module).
+//! * `derive` - Enables `#[derive(Signals)]`, which generates accessors for struct fields annotated with `#[signal]`.
+//! * `serde` - Enables recording emissions to a writer and replaying them later (see the [record] module), and
+//!   forwarding emissions to other processes (see the [bridge] and [remote] modules).
+//!
 //! ### Links
 //! * [Github](https://github.com/christiandaley/signals2/)
 //! * [Changelog](https://github.com/christiandaley/signals2/blob/master/CHANGES.md)
 
 #![deny(missing_docs)]
 
-use std::sync::{Arc, Weak, RwLock};
+use std::sync::{Arc, Weak, RwLock, atomic::{AtomicBool, AtomicUsize}};
 
 mod signal_core;
-use signal_core::{SignalCore};
+use signal_core::{SignalCore, SharedCore, WeakCore};
+
+mod wait;
+pub use wait::SignalReceiver;
+
+/// Defines signals that replay their most recent emissions to newly connected slots.
+pub mod replay;
+
+/// Defines observable properties that emit a signal when their value changes.
+pub mod property;
+
+/// Defines computed values that are derived from other observable values.
+pub mod computed;
+
+/// Defines collections that emit a signal describing every change made to them.
+pub mod collections;
+
+/// Defines the object-safe [UntypedSignal](untyped::UntypedSignal) trait.
+pub mod untyped;
+
+/// Defines an event bus that delivers events to subscribers based on the type of the event.
+pub mod event_bus;
+
+/// Defines a registry of signals that are identified by hierarchical paths.
+pub mod registry;
+
+/// Defines [SignalTransaction](transaction::SignalTransaction), which applies several changes to a signal at once.
+pub mod transaction;
+
+/// Defines [EmissionContext](context::EmissionContext), which gives slots information about the emission executing them.
+pub mod context;
+
+/// Defines the [ReentrancyPolicy](reentrancy::ReentrancyPolicy) that controls nested emissions of a signal.
+pub mod reentrancy;
+
+/// Defines utilities for testing code that uses signals, such as [SignalSpy](testing::SignalSpy) and [MockSlot](testing::MockSlot).
+pub mod testing;
+
+/// Defines [Stream](futures_core::Stream) and [Sink](futures_sink::Sink) adapters for signals. Requires the `futures` feature.
+#[cfg(feature = "futures")]
+pub mod stream;
+
+/// Defines [EmissionRecorder](record::EmissionRecorder) and [Replayer](record::Replayer) for recording emissions and replaying them later. Requires the `serde` feature.
+#[cfg(feature = "serde")]
+pub mod record;
+
+/// Defines [SignalBridge](bridge::SignalBridge), which forwards emissions to another process over a Unix domain socket. Requires the `serde` feature.
+#[cfg(all(feature = "serde", unix))]
+pub mod bridge;
+
+/// Defines the [Transport](remote::Transport) trait and [RemoteSignal](remote::RemoteSignal), which receives emissions from another process. Requires the `serde` feature.
+#[cfg(feature = "serde")]
+pub mod remote;
 
 /// Defines the combiner trait and several simple combiners that can be used.
 pub mod combiner;
@@ -32,15 +92,44 @@ use combiner::{Combiner, DefaultCombiner};
 /// Defines different `emit` traits for signals.
 pub mod emit;
 #[doc(inline)]
-pub use emit::{Emit0, Emit1, Emit2, Emit3, Emit4, Emit5, Emit6, Emit7, Emit8, Emit9, Emit10, Emit11, Emit12};
+pub use emit::{Emit, Emit0, Emit1, Emit2, Emit3, Emit4, Emit5, Emit6, Emit7, Emit8, Emit9, Emit10, Emit11, Emit12};
 
 /// Defines different `connect` traits for signals.
 pub mod connect;
 #[doc(inline)]
-pub use connect::{SharedConnectionBlock, Connection, ScopedConnection, Position, Group, 
-    Connect0, Connect1, Connect2, Connect3, Connect4, Connect5, Connect6, Connect7, Connect8,
+pub use connect::{SharedConnectionBlock, Connection, ScopedConnection, Position, Group, SlotControl,
+    ConnectionSet, ConnectionSetBlock, Trackable, TupleFn, Connect, Connect0, Connect1, Connect2, Connect3, Connect4, Connect5, Connect6, Connect7, Connect8,
     Connect9, Connect10, Connect11, Connect12};
 
+/// Derives accessors for signal fields. Requires the `derive` feature.
+#[cfg(feature = "derive")]
+pub use signals2_derive::Signals;
+
+/// Expands to the [Signal] type for slot functions with the given signature, which avoids having to write the
+/// arguments as a tuple. A combiner type and a group type may optionally follow the signature.
+/// # Example
+/// ```
+/// use signals2::*;
+/// use signals2::combiner::DefaultCombiner;
+///
+/// let sig: signal!(fn(i32) -> i32) = Signal::new(); // Signal<(i32,), i32>
+/// sig.connect(|x| x * 2);
+/// assert_eq!(sig.emit(4), Some(8));
+///
+/// let _: signal!(fn()) = Signal::<()>::new();
+/// let _: signal!(fn(String, usize)) = Signal::<(String, usize)>::new();
+/// let _: signal!(fn(u8), DefaultCombiner, usize) = Signal::<(u8,), (), DefaultCombiner, usize>::new();
+/// ```
+#[macro_export]
+macro_rules! signal {
+    (fn($($args:ty),* $(,)?) -> $ret:ty $(, $rest:ty)* $(,)?) => {
+        $crate::Signal<($($args,)*), $ret $(, $rest)*>
+    };
+    (fn($($args:ty),* $(,)?) $(, $rest:ty)* $(,)?) => {
+        $crate::Signal<($($args,)*), () $(, $rest)*>
+    };
+}
+
 /// A handle to a signal with a slot function signature of `Args -> R`. `C` defines the combiner used
 /// to generate a return value when `emit` is envoked. `G` defines the ordering of groups of slots. **Arguments given
 /// to the signal must implement `Clone`. If you need to emit a signal with an argument that doesn't implement clone, that
@@ -91,7 +180,7 @@ where
     C: Combiner<R> + 'static,
     G: Ord + Send + Sync + 'static
 {
-    core: Arc<RwLock<Arc<SignalCore<Args, R, C, G>>>>
+    core: SharedCore<Args, R, C, G>
 }
 
 impl<Args, R, C, G> Clone for Signal<Args, R, C, G>
@@ -143,9 +232,8 @@ where
 {
     /// Creates a new signal with a corresponding [Combiner].
     pub fn new_with_combiner(combiner: C) -> Self {
-        let core: SignalCore<Args, R, C, G> = SignalCore::new(combiner);
         Signal {
-            core: Arc::new(RwLock::new(Arc::new(core)))
+            core: Arc::new_cyclic(|shared| RwLock::new(Arc::new(SignalCore::new(combiner, shared.clone()))))
         }
     }
 
@@ -172,26 +260,115 @@ where
 
     /// Sets a new [Combiner] for the signal.
     pub fn set_combiner(&self, combiner: C) {
-        let mut lock = self.core.write().unwrap();
-        let mut new_core = (**lock).clone();
-        new_core.set_combiner(combiner);
-        *lock = Arc::new(new_core);
+        self.modify_core(|core| core.set_combiner(combiner));
     }
 
     /// Disconnects all slots from the signal. Will cause any existing [Connections](Connection) to enter a
     /// "disconnected" state.
     pub fn clear(&self) {
         self.core.read().unwrap().disconnect_all();
-        let mut lock = self.core.write().unwrap();
-        let mut new_core = (**lock).clone();
-        new_core.clear();
-        *lock = Arc::new(new_core);
+        self.modify_core(|core| core.clear());
     }
 
     /// Returns the number of connected slots for the signal.
     pub fn count(&self) -> usize {
         self.core.read().unwrap().count()
     }
+
+    /// Returns an ID that uniquely identifies the signal. Clones of a signal share the same ID.
+    pub fn id(&self) -> u64 {
+        self.core.read().unwrap().id()
+    }
+
+    /// Returns true if `self` and `other` are handles to the same signal, i.e. one is a clone of the other.
+    pub fn ptr_eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.core, &other.core)
+    }
+
+    /// Returns the number of [SharedConnectionBlocks](SharedConnectionBlock) currently blocking the entire signal.
+    pub fn blocker_count(&self) -> usize {
+        self.core.read().unwrap().blocker_count()
+    }
+
+    /// Returns true if the entire signal is currently blocked. Emitting a blocked signal executes none of its slots.
+    pub fn blocked(&self) -> bool {
+        self.core.read().unwrap().blocked()
+    }
+
+    #[must_use="shared connection blocks are automatically unblocked when dropped"]
+    /// Gets a [SharedConnectionBlock] that can be used to temporarily block every slot of the signal, including
+    /// slots that are connected while the block is active.
+    /// # Example
+    /// ```
+    /// use signals2::*;
+    ///
+    /// let sig: Signal<(), i32> = Signal::new();
+    /// sig.connect(|| 4);
+    /// let blocker = sig.shared_block(true);
+    /// assert_eq!(sig.emit(), None);
+    /// blocker.unblock();
+    /// assert_eq!(sig.emit(), Some(4));
+    /// ```
+    pub fn shared_block(&self, initially_blocking: bool) -> SharedConnectionBlock {
+        SharedConnectionBlock::new(self.core.read().unwrap().weak_blocker_count(), initially_blocking)
+    }
+
+    // Replaces the signal's core with a modified copy of it. Emissions that are already in progress
+    // continue to use the old core.
+    pub(crate) fn modify_core<T>(&self, f: impl FnOnce(&mut SignalCore<Args, R, C, G>) -> T) -> T {
+        let mut lock = self.core.write().unwrap();
+        let mut new_core = (**lock).clone();
+        let res = f(&mut new_core);
+        *lock = Arc::new(new_core);
+        res
+    }
+
+    // Creates the function that the signal core uses to build the connection for a newly connected slot.
+    pub(crate) fn make_conn(&self) -> impl FnOnce(Weak<AtomicBool>, Weak<AtomicUsize>, u64) -> Connection {
+        Self::conn_factory(Arc::downgrade(&self.core), self.id())
+    }
+
+    // Like `make_conn`, but does not need to lock the signal's core.
+    pub(crate) fn conn_factory(weak_core: WeakCore<Args, R, C, G>, signal_id: u64) -> impl FnOnce(Weak<AtomicBool>, Weak<AtomicUsize>, u64) -> Connection {
+        let cleanup = move || {
+            if let Some(core) = weak_core.upgrade() {
+                Signal { core }.modify_core(|core| core.cleanup());
+            }
+        };
+
+        move |connected, blocker_count, slot_id| {
+            Connection::new(connected, blocker_count, Arc::new(cleanup), signal_id, slot_id)
+        }
+    }
+
+    // Returns the signal's current core. Emitting the returned core only executes the slots that
+    // were connected at the time `snapshot` was called.
+    pub(crate) fn snapshot(&self) -> Arc<SignalCore<Args, R, C, G>> {
+        self.core.read().unwrap().clone()
+    }
+
+    // Emits the signal with the given tuple of arguments.
+    pub(crate) fn emit_args(&self, args: &Args) -> C::Output {
+        self.snapshot().emit(args)
+    }
+
+    // Connects a slot that is skipped by the combiner whenever it returns `None`.
+    pub(crate) fn connect_partial<F>(&self, f: F, group: Group<G>, pos: Position) -> Connection
+    where
+        F: Fn(&Connection, Args) -> Option<R> + Send + Sync + 'static
+    {
+        let make_conn = self.make_conn();
+        self.modify_core(|core| core.connect_partial(f, group, pos, make_conn))
+    }
+
+    // Connects a contextual slot that is skipped by the combiner whenever it returns `None`.
+    pub(crate) fn connect_partial_with_context<F>(&self, f: F, group: Group<G>, pos: Position) -> Connection
+    where
+        F: Fn(&context::EmissionContext<G>, Args) -> Option<R> + Send + Sync + 'static
+    {
+        let make_conn = self.make_conn();
+        self.modify_core(|core| core.connect_partial_with_context(f, group, pos, make_conn))
+    }
 }
 
 impl<Args, R, C, G> Signal<Args, R, C, G>
@@ -235,7 +412,7 @@ where
     C: Combiner<R> + 'static,
     G: Ord + Send + Sync + 'static
 {
-    weak_core: Weak<RwLock<Arc<SignalCore<Args, R, C, G>>>>
+    weak_core: WeakCore<Args, R, C, G>
 }
 
 impl<Args, R, C, G> Clone for WeakSignal<Args, R, C, G>
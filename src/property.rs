@@ -0,0 +1,211 @@
+// Copyright Christian Daley 2021
+// Copyright Frank Mori Hess 2007-2008.
+// Distributed under the Boost Software License, Version 1.0. 
+// See http://www.boost.org/LICENSE_1_0.txt
+
+use std::cell::RefCell;
+use std::sync::{Arc, Weak, RwLock};
+
+use crate::{Signal, ConnectHandle, ScopedConnection};
+use crate::computed;
+use crate::connect::Connect2;
+use crate::emit::Emit2;
+
+thread_local! {
+    // The bindings that are currently propagating a value on this thread, identified by address.
+    static PROPAGATING: RefCell<Vec<usize>> = const { RefCell::new(Vec::new()) };
+}
+
+struct PropertyInner<T>
+where
+    T: Clone + PartialEq + Send + Sync + 'static
+{
+    value: RwLock<T>,
+    changed: Signal<(T, T)>
+}
+
+/// A value that notifies observers when it changes. Every change emits a signal with the old value
+/// and the new value, in that order. A value is only considered changed if the new value is not equal to
+/// the old one.
+///
+/// A `Property` is a handle to its value. Cloning a property results in two handles to the same value.
+/// # Example
+/// ```
+/// use signals2::*;
+/// use signals2::property::Property;
+///
+/// let prop = Property::new(1);
+/// prop.changed().connect(|old, new| println!("{} -> {}", old, new));
+/// prop.set(2); // prints "1 -> 2"
+/// prop.set(2); // prints nothing, the value did not change
+/// prop.modify(|x| *x *= 5); // prints "2 -> 10"
+/// assert_eq!(prop.get(), 10);
+/// ```
+pub struct Property<T>
+where
+    T: Clone + PartialEq + Send + Sync + 'static
+{
+    inner: Arc<PropertyInner<T>>
+}
+
+impl<T> Clone for Property<T>
+where
+    T: Clone + PartialEq + Send + Sync + 'static
+{
+    /// Creates another handle to the same value.
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone()
+        }
+    }
+}
+
+impl<T> Default for Property<T>
+where
+    T: Clone + PartialEq + Default + Send + Sync + 'static
+{
+    /// Creates a property holding `T::default()`.
+    fn default() -> Self {
+        Self::new(T::default())
+    }
+}
+
+impl<T> Property<T>
+where
+    T: Clone + PartialEq + Send + Sync + 'static
+{
+    /// Creates a new property holding `value`.
+    pub fn new(value: T) -> Self {
+        Property {
+            inner: Arc::new(PropertyInner {
+                value: RwLock::new(value),
+                changed: Signal::new()
+            })
+        }
+    }
+
+    /// Returns a clone of the current value. When called while a [Computed](crate::computed::Computed) is being
+    /// evaluated, the property is recorded as one of its dependencies.
+    pub fn get(&self) -> T {
+        let handle = self.changed();
+        computed::track(Arc::as_ptr(&self.inner) as *const () as usize, move |notify| {
+            handle.connect(move |_, _| notify()).scoped()
+        });
+
+        self.inner.value.read().unwrap().clone()
+    }
+
+    /// Replaces the current value with `value`. If the two values are not equal the change signal is
+    /// emitted and `true` is returned, otherwise nothing happens and `false` is returned.
+    pub fn set(&self, value: T) -> bool {
+        self.modify(move |current| *current = value)
+    }
+
+    /// Modifies the current value in place. If the value after `f` returns is not equal to the value before
+    /// `f` was called the change signal is emitted and `true` is returned, otherwise `false` is returned.
+    /// The value is locked while `f` runs, so `f` must not access the property.
+    pub fn modify<F>(&self, f: F) -> bool
+    where
+        F: FnOnce(&mut T)
+    {
+        let mut lock = self.inner.value.write().unwrap();
+        let old = lock.clone();
+        f(&mut lock);
+        if *lock == old {
+            return false;
+        }
+
+        let new = lock.clone();
+        std::mem::drop(lock);
+        self.inner.changed.emit(old, new);
+        true
+    }
+
+    /// Returns a [ConnectHandle] for the signal that is emitted with the old and new values whenever
+    /// the value changes.
+    pub fn changed(&self) -> ConnectHandle<(T, T)> {
+        self.inner.changed.get_connect_handle()
+    }
+
+    /// Makes this property follow `source`. The property immediately takes on the value of `source`
+    /// and is updated every time `source` changes, until the returned [PropertyBinding] is dropped.
+    /// Neither property keeps the other alive.
+    /// # Example
+    /// ```
+    /// use signals2::property::Property;
+    ///
+    /// let source = Property::new("a");
+    /// let target = Property::new("b");
+    /// let binding = target.bind(&source);
+    /// assert_eq!(target.get(), "a");
+    ///
+    /// source.set("c");
+    /// assert_eq!(target.get(), "c");
+    ///
+    /// std::mem::drop(binding);
+    /// source.set("d");
+    /// assert_eq!(target.get(), "c");
+    /// ```
+    #[must_use = "the binding is removed when the PropertyBinding is dropped"]
+    pub fn bind(&self, source: &Property<T>) -> PropertyBinding {
+        let guard = Arc::new(());
+        self.set(source.get());
+        PropertyBinding {
+            _connections: vec!(forward(source, self, &guard)),
+            _guard: guard
+        }
+    }
+
+    /// Binds this property and `other` to each other so that a change to either one is applied to the other.
+    /// This property immediately takes on the value of `other`. Changes that are applied by the binding itself
+    /// are not propagated back, so the binding can not cause a feedback loop. The binding is removed when the
+    /// returned [PropertyBinding] is dropped.
+    #[must_use = "the binding is removed when the PropertyBinding is dropped"]
+    pub fn bind_bidirectional(&self, other: &Property<T>) -> PropertyBinding {
+        let guard = Arc::new(());
+        self.set(other.get());
+        PropertyBinding {
+            _connections: vec!(forward(other, self, &guard), forward(self, other, &guard)),
+            _guard: guard
+        }
+    }
+}
+
+// Connects a slot to `source` that sets `target` whenever `source` changes. Changes made while the
+// binding identified by `guard` is already propagating a value on the current thread are ignored.
+fn forward<T>(source: &Property<T>, target: &Property<T>, guard: &Arc<()>) -> ScopedConnection
+where
+    T: Clone + PartialEq + Send + Sync + 'static
+{
+    let weak_target: Weak<PropertyInner<T>> = Arc::downgrade(&target.inner);
+    let binding_id = Arc::as_ptr(guard) as usize;
+
+    source.inner.changed.connect_extended(move |conn, _, new| {
+        let target = match weak_target.upgrade() {
+            Some(inner) => Property { inner },
+            None => return conn.disconnect()
+        };
+
+        let propagating = PROPAGATING.with(|ids| ids.borrow().contains(&binding_id));
+        if propagating {
+            return;
+        }
+
+        PROPAGATING.with(|ids| ids.borrow_mut().push(binding_id));
+        target.set(new);
+        PROPAGATING.with(|ids| ids.borrow_mut().retain(|id| *id != binding_id));
+    }).scoped()
+}
+
+/// A binding between two [Properties](Property), created by [Property::bind] or [Property::bind_bidirectional].
+/// The binding is removed when it is dropped.
+pub struct PropertyBinding {
+    _connections: Vec<ScopedConnection>,
+    // Only used for its address, which identifies the binding.
+    _guard: Arc<()>
+}
+
+impl PropertyBinding {
+    /// Removes the binding. Equivalent to dropping it.
+    pub fn unbind(self) {}
+}
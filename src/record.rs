@@ -0,0 +1,322 @@
+// Copyright Christian Daley 2021
+// Copyright Frank Mori Hess 2007-2008.
+// Distributed under the Boost Software License, Version 1.0. 
+// See http://www.boost.org/LICENSE_1_0.txt
+
+use std::error::Error;
+use std::fmt;
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::marker::PhantomData;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use serde::{Serialize, Deserialize, de::DeserializeOwned};
+
+use crate::{Signal, Group, Position, ScopedConnection, Emit};
+use crate::combiner::Combiner;
+
+/// The largest frame, in bytes, that can be written or read by the length-prefixed [Format::Binary] encoding, the
+/// [TcpTransport](crate::remote::TcpTransport) and the [bridge](crate::bridge). A length prefix above this limit is
+/// rejected with [io::ErrorKind::InvalidData] before anything is allocated for the frame.
+pub const MAX_FRAME_SIZE: usize = 64 * 1024 * 1024;
+
+/// The format in which emissions are written by an [EmissionRecorder] and read by a [Replayer].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Format {
+    /// Every emission is written as a JSON object on its own line.
+    JsonLines,
+    /// Every emission is encoded with bincode and prefixed with its length as a little-endian `u32`. Emissions larger
+    /// than [MAX_FRAME_SIZE] bytes cannot be written or read.
+    Binary
+}
+
+/// Controls how quickly a [Replayer] re-emits the emissions it has read.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Timing {
+    /// Every emission is replayed immediately after the previous one.
+    Fast,
+    /// Emissions are replayed at the same intervals at which they were recorded.
+    Original
+}
+
+/// A single emission as written by an [EmissionRecorder].
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct RecordedEmission<Args> {
+    /// The position of the emission in the recording, starting at 0.
+    pub seq: u64,
+    /// The time between the creation of the recorder and the emission.
+    pub elapsed: Duration,
+    /// The arguments the signal was emitted with.
+    pub args: Args
+}
+
+/// Error returned when an emission cannot be recorded or replayed.
+#[derive(Debug)]
+pub enum RecordError {
+    /// Reading from or writing to the underlying reader or writer failed.
+    Io(io::Error),
+    /// An emission could not be encoded or decoded as JSON.
+    Json(serde_json::Error),
+    /// An emission could not be encoded or decoded with bincode.
+    Binary(bincode::Error)
+}
+
+impl fmt::Display for RecordError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RecordError::Io(err) => write!(f, "i/o error: {}", err),
+            RecordError::Json(err) => write!(f, "json error: {}", err),
+            RecordError::Binary(err) => write!(f, "binary encoding error: {}", err)
+        }
+    }
+}
+
+impl Error for RecordError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            RecordError::Io(err) => Some(err),
+            RecordError::Json(err) => Some(err),
+            RecordError::Binary(err) => Some(err)
+        }
+    }
+}
+
+impl From<io::Error> for RecordError {
+    fn from(err: io::Error) -> Self {
+        RecordError::Io(err)
+    }
+}
+
+impl From<serde_json::Error> for RecordError {
+    fn from(err: serde_json::Error) -> Self {
+        RecordError::Json(err)
+    }
+}
+
+impl From<bincode::Error> for RecordError {
+    fn from(err: bincode::Error) -> Self {
+        RecordError::Binary(err)
+    }
+}
+
+// Writes `bytes` prefixed with their length as a little-endian u32.
+pub(crate) fn write_frame<W: Write + ?Sized>(writer: &mut W, bytes: &[u8]) -> io::Result<()> {
+    if bytes.len() > MAX_FRAME_SIZE {
+        return Err(io::Error::new(io::ErrorKind::InvalidInput, "frame is too large"));
+    }
+
+    let len = bytes.len() as u32;
+    writer.write_all(&len.to_le_bytes())?;
+    writer.write_all(bytes)
+}
+
+// Reads a frame written by `write_frame`. Returns `None` if the reader is at the end of its input.
+pub(crate) fn read_frame<R: Read + ?Sized>(reader: &mut R) -> io::Result<Option<Vec<u8>>> {
+    let mut len = [0u8; 4];
+    let mut read = 0;
+    while read < len.len() {
+        match reader.read(&mut len[read..]) {
+            Ok(0) if read == 0 => return Ok(None),
+            Ok(0) => return Err(io::ErrorKind::UnexpectedEof.into()),
+            Ok(n) => read += n,
+            Err(err) if err.kind() == io::ErrorKind::Interrupted => {},
+            Err(err) => return Err(err)
+        }
+    }
+
+    // the prefix is untrusted, so it is checked before allocating the frame
+    let len = u32::from_le_bytes(len) as usize;
+    if len > MAX_FRAME_SIZE {
+        return Err(io::Error::new(io::ErrorKind::InvalidData, "frame is too large"));
+    }
+
+    let mut bytes = vec!(0; len);
+    reader.read_exact(&mut bytes)?;
+    Ok(Some(bytes))
+}
+
+struct RecorderState<W> {
+    writer: Option<W>,
+    format: Format,
+    start: Instant,
+    next_seq: u64,
+    error: Option<RecordError>
+}
+
+impl<W: Write> RecorderState<W> {
+    fn record<Args: Serialize>(&mut self, args: Args) -> Result<(), RecordError> {
+        let emission = RecordedEmission {
+            seq: self.next_seq,
+            elapsed: self.start.elapsed(),
+            args
+        };
+
+        let writer = match &mut self.writer {
+            Some(writer) => writer,
+            None => return Ok(())
+        };
+
+        match self.format {
+            Format::JsonLines => {
+                serde_json::to_writer(&mut *writer, &emission)?;
+                writer.write_all(b"\n")?;
+            },
+            Format::Binary => write_frame(writer, &bincode::serialize(&emission)?)?
+        }
+
+        self.next_seq += 1;
+        Ok(())
+    }
+}
+
+/// Writes every emission of a signal, along with the time at which it happened, to a writer. The recorder is
+/// connected to the signal at the back of [Group::Back] and does not contribute to the signal's return value. It is
+/// disconnected when it is dropped or [finished](EmissionRecorder::finish). Requires the `serde` feature.
+///
+/// If writing an emission fails, the recorder stops recording and the error is returned by `finish`.
+/// # Example
+/// ```
+/// use signals2::*;
+/// use signals2::record::{EmissionRecorder, Replayer, Format, Timing};
+///
+/// let sig: Signal<(i32, String)> = Signal::new();
+/// let recorder = EmissionRecorder::new(&sig, Vec::new(), Format::JsonLines);
+/// sig.emit(1, "one".to_string());
+/// sig.emit(2, "two".to_string());
+/// let log = recorder.finish().unwrap();
+///
+/// let other: Signal<(i32, String), i32> = Signal::new();
+/// other.connect(|x, _| x);
+/// let replayer: Replayer<(i32, String)> = Replayer::read(log.as_slice(), Format::JsonLines).unwrap();
+/// assert_eq!(replayer.replay(&other, Timing::Fast), vec!(Some(1), Some(2)));
+/// ```
+pub struct EmissionRecorder<Args, W> {
+    state: Arc<Mutex<RecorderState<W>>>,
+    conn: ScopedConnection,
+    _args: PhantomData<fn(Args)>
+}
+
+impl<Args, W> EmissionRecorder<Args, W>
+where
+    Args: Serialize + Clone + Send + 'static,
+    W: Write + Send + 'static
+{
+    /// Creates a recorder that writes the emissions of `sig` to `writer` in the given format.
+    pub fn new<R, C, G>(sig: &Signal<Args, R, C, G>, writer: W, format: Format) -> Self
+    where
+        R: 'static,
+        C: Combiner<R> + 'static,
+        G: Ord + Send + Sync + 'static
+    {
+        let state = Arc::new(Mutex::new(RecorderState {
+            writer: Some(writer),
+            format,
+            start: Instant::now(),
+            next_seq: 0,
+            error: None
+        }));
+
+        let slot_state = state.clone();
+        let conn = sig.connect_partial(move |_, args| {
+            let mut state = slot_state.lock().unwrap();
+            if state.error.is_none() {
+                if let Err(err) = state.record(args) {
+                    state.error = Some(err);
+                }
+            }
+
+            None
+        }, Group::Back, Position::Back).scoped();
+
+        EmissionRecorder {
+            state,
+            conn,
+            _args: PhantomData
+        }
+    }
+
+    /// Returns the number of emissions that have been written.
+    pub fn count(&self) -> u64 {
+        self.state.lock().unwrap().next_seq
+    }
+
+    /// Disconnects the recorder from the signal, flushes the writer and returns it. Returns the first error that
+    /// occurred while recording, if any.
+    pub fn finish(self) -> Result<W, RecordError> {
+        std::mem::drop(self.conn);
+        let mut state = self.state.lock().unwrap();
+        if let Some(err) = state.error.take() {
+            return Err(err);
+        }
+
+        let mut writer = state.writer.take().unwrap();
+        writer.flush()?;
+        Ok(writer)
+    }
+}
+
+/// Re-emits emissions that were written by an [EmissionRecorder]. Requires the `serde` feature.
+pub struct Replayer<Args> {
+    emissions: Vec<RecordedEmission<Args>>
+}
+
+impl<Args> Replayer<Args>
+where
+    Args: DeserializeOwned + Clone
+{
+    /// Reads every emission from `reader`, which must contain emissions written in the given format.
+    pub fn read<Rd: Read>(reader: Rd, format: Format) -> Result<Self, RecordError> {
+        let mut emissions = Vec::new();
+        match format {
+            Format::JsonLines => {
+                for line in BufReader::new(reader).lines() {
+                    let line = line?;
+                    if !line.trim().is_empty() {
+                        emissions.push(serde_json::from_str(&line)?);
+                    }
+                }
+            },
+            Format::Binary => {
+                let mut reader = BufReader::new(reader);
+                while let Some(frame) = read_frame(&mut reader)? {
+                    emissions.push(bincode::deserialize(&frame)?);
+                }
+            }
+        }
+
+        Ok(Replayer { emissions })
+    }
+
+    /// Returns the emissions that were read, in the order in which they were recorded.
+    pub fn emissions(&self) -> &[RecordedEmission<Args>] {
+        &self.emissions
+    }
+
+    /// Emits `target` once for every recorded emission, in order, and returns the output of each emission. `target`
+    /// can be anything that implements [Emit], such as a [Signal] or an [EmitHandle](crate::EmitHandle). With
+    /// [Timing::Original] the current thread sleeps between emissions so that they are spaced as they were when they
+    /// were recorded.
+    pub fn replay<S>(&self, target: &S, timing: Timing) -> Vec<S::Output>
+    where
+        S: Emit<Args> + ?Sized
+    {
+        let start = Instant::now();
+        let first = self.emissions.first().map(|emission| emission.elapsed).unwrap_or_default();
+
+        self.emissions
+            .iter()
+            .map(|emission| {
+                if timing == Timing::Original {
+                    let due = start + emission.elapsed.saturating_sub(first);
+                    let now = Instant::now();
+                    if due > now {
+                        thread::sleep(due - now);
+                    }
+                }
+
+                target.emit_tuple(emission.args.clone())
+            })
+            .collect()
+    }
+}
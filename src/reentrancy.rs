@@ -0,0 +1,249 @@
+// Copyright Christian Daley 2021
+// Copyright Frank Mori Hess 2007-2008.
+// Distributed under the Boost Software License, Version 1.0. 
+// See http://www.boost.org/LICENSE_1_0.txt
+
+use std::cell::RefCell;
+use std::collections::{HashMap, VecDeque};
+use std::error::Error;
+use std::fmt;
+use std::sync::Arc;
+use std::thread;
+
+use crate::Signal;
+use crate::combiner::Combiner;
+
+/// Determines what happens when a signal is emitted while it is already being emitted on the same thread, for
+/// example by one of its own slots or by a cycle of signals that forward to each other.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum ReentrancyPolicy {
+    /// Nested emissions execute the signal's slots immediately. This is the default.
+    #[default]
+    Allow,
+    /// Nested emissions are ignored. No slots are executed and the combiner is invoked with no values.
+    Skip,
+    /// Nested emissions are queued and executed, in order, after the outermost emission of the signal on the current
+    /// thread has finished. The nested emission itself executes no slots and the combiner is invoked with no values.
+    /// A queued emission executes the slots that are connected when it runs, and is discarded if the signal has been
+    /// dropped by then. Emissions queued by the slots of a [parallel emission](crate::Emit::emit_tuple_parallel) are
+    /// executed by the worker thread that queued them once it has executed its share of the slots.
+    Queue
+}
+
+/// The reason an emission did not execute any slots. Returned by [Signal::try_emit_tuple].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ReentrancyError {
+    /// The emission was nested and the signal's policy is [ReentrancyPolicy::Skip].
+    Skipped,
+    /// The emission was nested and has been queued because the signal's policy is [ReentrancyPolicy::Queue].
+    Queued,
+    /// The emission would have exceeded the signal's maximum depth.
+    MaxDepthExceeded {
+        /// The depth the emission would have had.
+        depth: usize
+    }
+}
+
+impl fmt::Display for ReentrancyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ReentrancyError::Skipped => write!(f, "nested emission was skipped"),
+            ReentrancyError::Queued => write!(f, "nested emission was queued"),
+            ReentrancyError::MaxDepthExceeded { depth } => write!(f, "emission depth {} exceeds the maximum depth", depth)
+        }
+    }
+}
+
+impl Error for ReentrancyError {}
+
+type DepthHook = Arc<dyn Fn(usize) + Send + Sync>;
+
+// The reentrancy settings of a signal, stored in its core.
+#[derive(Clone, Default)]
+pub(crate) struct ReentrancyConfig {
+    pub policy: ReentrancyPolicy,
+    pub max_depth: Option<usize>,
+    pub hook: Option<DepthHook>
+}
+
+#[derive(Default)]
+struct EmissionState {
+    depth: usize,
+    queue: VecDeque<Box<dyn FnOnce()>>
+}
+
+thread_local! {
+    // The emissions in progress on the current thread, keyed by signal ID.
+    static EMISSIONS: RefCell<HashMap<u64, EmissionState>> = RefCell::new(HashMap::new());
+}
+
+// Returns the number of emissions of the given signal that are in progress on the current thread.
+pub(crate) fn depth(signal_id: u64) -> usize {
+    EMISSIONS.with(|emissions| emissions.borrow().get(&signal_id).map_or(0, |state| state.depth))
+}
+
+pub(crate) fn enqueue(signal_id: u64, job: Box<dyn FnOnce()>) {
+    EMISSIONS.with(|emissions| emissions.borrow_mut().entry(signal_id).or_default().queue.push_back(job));
+}
+
+pub(crate) fn dequeue(signal_id: u64) -> Option<Box<dyn FnOnce()>> {
+    EMISSIONS.with(|emissions| {
+        let mut emissions = emissions.borrow_mut();
+        let job = emissions.get_mut(&signal_id).and_then(|state| state.queue.pop_front());
+        if job.is_none() {
+            emissions.remove(&signal_id);
+        }
+
+        job
+    })
+}
+
+// Executes `f` on a worker thread of a parallel emission as though the emission were in progress on the worker at
+// `depth`, its depth on the thread that started it, so that nested emissions from the worker's slots are subject to
+// the signal's reentrancy policy and maximum depth. Emissions that the worker's slots queue are executed by the worker
+// once `f` has returned, unless the worker is itself still emitting the signal.
+pub(crate) fn inherit<T>(signal_id: u64, depth: usize, f: impl FnOnce() -> T) -> T {
+    let result = {
+        let _guard = DepthGuard::raise(signal_id, depth);
+        f()
+    };
+
+    if self::depth(signal_id) == 0 {
+        while let Some(job) = dequeue(signal_id) {
+            job();
+        }
+    }
+
+    result
+}
+
+// Marks an emission of a signal as in progress on the current thread for as long as it is alive.
+pub(crate) struct DepthGuard {
+    signal_id: u64,
+    added: usize
+}
+
+impl DepthGuard {
+    pub fn enter(signal_id: u64) -> Self {
+        EMISSIONS.with(|emissions| emissions.borrow_mut().entry(signal_id).or_default().depth += 1);
+        DepthGuard { signal_id, added: 1 }
+    }
+
+    // Raises the depth of the signal's emissions on the current thread to at least `depth`. A rayon worker may already
+    // be emitting the signal, and the thread that started a parallel emission may execute some of its slots itself.
+    fn raise(signal_id: u64, depth: usize) -> Self {
+        let added = EMISSIONS.with(|emissions| {
+            let mut emissions = emissions.borrow_mut();
+            let state = emissions.entry(signal_id).or_default();
+            let added = depth.saturating_sub(state.depth);
+            state.depth += added;
+            added
+        });
+
+        DepthGuard { signal_id, added }
+    }
+}
+
+impl Drop for DepthGuard {
+    fn drop(&mut self) {
+        let _ = EMISSIONS.try_with(|emissions| {
+            let mut emissions = emissions.borrow_mut();
+            if let Some(state) = emissions.get_mut(&self.signal_id) {
+                state.depth -= self.added;
+                // a panicking emission discards the emissions it queued
+                if state.depth == 0 && (state.queue.is_empty() || thread::panicking()) {
+                    emissions.remove(&self.signal_id);
+                }
+            }
+        });
+    }
+}
+
+impl<Args, R, C, G> Signal<Args, R, C, G>
+where
+    Args: Clone + 'static,
+    R: 'static,
+    C: Combiner<R> + 'static,
+    G: Ord + Send + Sync + 'static
+{
+    /// Sets the signal's [ReentrancyPolicy].
+    /// # Example
+    /// ```
+    /// use signals2::*;
+    /// use signals2::reentrancy::ReentrancyPolicy;
+    /// use std::sync::{Arc, Mutex};
+    ///
+    /// let sig: Signal<(i32,)> = Signal::new();
+    /// sig.set_reentrancy_policy(ReentrancyPolicy::Queue);
+    ///
+    /// let order = Arc::new(Mutex::new(Vec::new()));
+    /// let order_clone = order.clone();
+    /// let emit_handle = sig.get_emit_handle();
+    /// sig.connect(move |x| {
+    ///     order_clone.lock().unwrap().push(x);
+    ///     if x < 3 {
+    ///         emit_handle.emit(x + 1);
+    ///     }
+    ///     order_clone.lock().unwrap().push(-x);
+    /// });
+    ///
+    /// sig.emit(1);
+    /// assert_eq!(*order.lock().unwrap(), vec!(1, -1, 2, -2, 3, -3));
+    /// ```
+    pub fn set_reentrancy_policy(&self, policy: ReentrancyPolicy) {
+        self.modify_core(|core| core.reentrancy_mut().policy = policy);
+    }
+
+    /// Returns the signal's [ReentrancyPolicy].
+    pub fn reentrancy_policy(&self) -> ReentrancyPolicy {
+        self.snapshot().reentrancy().policy
+    }
+
+    /// Sets the maximum number of emissions of the signal that may be in progress on a single thread at once,
+    /// counting the outermost emission. An emission that would exceed the maximum executes no slots, and the combiner
+    /// is invoked with no values. `None`, the default, means there is no limit.
+    ///
+    /// Depth is tracked per thread, so slots executed on other threads by `emit_parallel` start a new count.
+    /// # Example
+    /// ```
+    /// use signals2::*;
+    ///
+    /// let sig: Signal<(), i32, combiner::SumCombiner> = Signal::new();
+    /// sig.set_max_depth(Some(3));
+    ///
+    /// let emit_handle = sig.get_emit_handle();
+    /// sig.connect(move || 1 + emit_handle.emit().unwrap()); // would recurse forever without a maximum depth
+    /// assert_eq!(sig.emit(), 3);
+    /// ```
+    pub fn set_max_depth(&self, max_depth: Option<usize>) {
+        self.modify_core(|core| core.reentrancy_mut().max_depth = max_depth);
+    }
+
+    /// Returns the signal's maximum depth.
+    pub fn max_depth(&self) -> Option<usize> {
+        self.snapshot().reentrancy().max_depth
+    }
+
+    /// Sets a function that is invoked with the would-be depth whenever an emission is refused because it would exceed
+    /// the signal's maximum depth. The hook is invoked on the emitting thread.
+    pub fn set_max_depth_hook<F>(&self, hook: F)
+    where
+        F: Fn(usize) + Send + Sync + 'static
+    {
+        self.modify_core(|core| core.reentrancy_mut().hook = Some(Arc::new(hook)));
+    }
+
+    /// Returns the number of emissions of the signal that are in progress on the current thread. Emissions are only
+    /// counted while the signal has a [ReentrancyPolicy] other than [Allow](ReentrancyPolicy::Allow), a maximum depth,
+    /// or slots connected with an [EmissionContext](crate::context::EmissionContext). Otherwise emitting the signal
+    /// skips the bookkeeping and this returns 0.
+    pub fn depth(&self) -> usize {
+        depth(self.id())
+    }
+
+    /// Like [emit_tuple](crate::Emit::emit_tuple), but returns an error instead of the combiner's output for an
+    /// emission that executed no slots because of the signal's [ReentrancyPolicy] or maximum depth.
+    pub fn try_emit_tuple(&self, args: Args) -> Result<C::Output, ReentrancyError> {
+        self.snapshot().try_emit(&args)
+    }
+}
@@ -0,0 +1,326 @@
+// Copyright Christian Daley 2021
+// Copyright Frank Mori Hess 2007-2008.
+// Distributed under the Boost Software License, Version 1.0. 
+// See http://www.boost.org/LICENSE_1_0.txt
+
+use std::any::{Any, TypeId};
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt;
+use std::sync::{Arc, Weak, Mutex, RwLock};
+
+use crate::{Signal, Group, Position, ScopedConnection};
+
+/// The error returned when a [SignalRegistry] operation fails.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum RegistryError {
+    /// The path is empty, contains an empty segment, or contains a wildcard.
+    InvalidPath(String),
+    /// The pattern is empty, contains an empty segment, or uses a wildcard incorrectly.
+    InvalidPattern(String),
+    /// No signal is registered under the path.
+    NotFound(String),
+    /// A signal is registered under the path, but its arguments are of a different type.
+    TypeMismatch {
+        /// The path of the signal.
+        path: String,
+        /// The argument type of the registered signal.
+        registered: &'static str,
+        /// The argument type that was requested.
+        requested: &'static str
+    }
+}
+
+impl fmt::Display for RegistryError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RegistryError::InvalidPath(path) => write!(f, "invalid signal path \"{}\"", path),
+            RegistryError::InvalidPattern(pattern) => write!(f, "invalid signal pattern \"{}\"", pattern),
+            RegistryError::NotFound(path) => write!(f, "no signal is registered under \"{}\"", path),
+            RegistryError::TypeMismatch { path, registered, requested } => write!(
+                f,
+                "the signal registered under \"{}\" takes {}, not {}",
+                path,
+                registered,
+                requested
+            )
+        }
+    }
+}
+
+impl Error for RegistryError {}
+
+enum Segment {
+    Literal(String),
+    // `*`, matches exactly one segment
+    Any,
+    // `#`, matches all remaining segments
+    Rest
+}
+
+struct Pattern {
+    segments: Vec<Segment>
+}
+
+impl Pattern {
+    fn parse(pattern: &str) -> Result<Self, RegistryError> {
+        let invalid = || RegistryError::InvalidPattern(pattern.to_string());
+        let parts: Vec<&str> = pattern.split('/').collect();
+        let mut segments = Vec::with_capacity(parts.len());
+
+        for (i, part) in parts.iter().enumerate() {
+            let segment = match *part {
+                "" => return Err(invalid()),
+                "*" => Segment::Any,
+                "#" if i == parts.len() - 1 => Segment::Rest,
+                _ if part.contains(['*', '#']) => return Err(invalid()),
+                _ => Segment::Literal(part.to_string())
+            };
+
+            segments.push(segment);
+        }
+
+        Ok(Pattern { segments })
+    }
+
+    fn matches(&self, path: &str) -> bool {
+        let mut parts = path.split('/');
+        for segment in self.segments.iter() {
+            match (segment, parts.next()) {
+                (Segment::Rest, _) => return true,
+                (Segment::Any, Some(_)) => {},
+                (Segment::Literal(literal), Some(part)) if literal == part => {},
+                _ => return false
+            }
+        }
+
+        parts.next().is_none()
+    }
+}
+
+fn validate_path(path: &str) -> Result<(), RegistryError> {
+    if path.split('/').any(|part| part.is_empty() || part.contains(['*', '#'])) {
+        Err(RegistryError::InvalidPath(path.to_string()))
+    } else {
+        Ok(())
+    }
+}
+
+struct Entry {
+    type_id: TypeId,
+    type_name: &'static str,
+    // a Signal<Args>
+    signal: Box<dyn Any + Send + Sync>
+}
+
+impl Entry {
+    fn downcast<Args>(&self, path: &str) -> Result<Signal<Args>, RegistryError>
+    where
+        Args: Clone + Send + Sync + 'static
+    {
+        self.signal
+            .downcast_ref::<Signal<Args>>()
+            .cloned()
+            .ok_or_else(|| RegistryError::TypeMismatch {
+                path: path.to_string(),
+                registered: self.type_name,
+                requested: std::any::type_name::<Args>()
+            })
+    }
+}
+
+// A pattern subscription, type erased so that the registry can attach it to newly registered signals.
+trait Subscriber: Send + Sync {
+    fn attach(&self, path: &str, entry: &Entry);
+}
+
+struct PatternSubscriber<Args, F>
+where
+    Args: Clone + Send + Sync + 'static,
+    F: Fn(&str, Args) + Send + Sync + 'static
+{
+    pattern: Pattern,
+    f: Arc<F>,
+    connections: Mutex<Vec<ScopedConnection>>,
+    _args: std::marker::PhantomData<fn(Args)>
+}
+
+impl<Args, F> Subscriber for PatternSubscriber<Args, F>
+where
+    Args: Clone + Send + Sync + 'static,
+    F: Fn(&str, Args) + Send + Sync + 'static
+{
+    fn attach(&self, path: &str, entry: &Entry) {
+        if entry.type_id != TypeId::of::<Args>() || !self.pattern.matches(path) {
+            return;
+        }
+
+        if let Ok(signal) = entry.downcast::<Args>(path) {
+            let f = self.f.clone();
+            let path = path.to_string();
+            let conn = signal.connect_partial(move |_, args| {
+                f(&path, args);
+                Some(())
+            }, Group::Back, Position::Back);
+
+            self.connections.lock().unwrap().push(conn.scoped());
+        }
+    }
+}
+
+#[derive(Default)]
+struct RegistryState {
+    signals: HashMap<String, Entry>,
+    subscribers: Vec<Weak<dyn Subscriber>>
+}
+
+/// A collection of [Signals](Signal) registered under hierarchical, `/` separated paths such as
+/// `"editor/buffer/saved"`. Registered signals can be looked up by path, and slots can be subscribed to
+/// every signal whose path matches a pattern. Two wildcards may be used in a pattern: `*` matches exactly one
+/// segment of a path and `#`, which must be the last segment of a pattern, matches all remaining segments
+/// (including none). A pattern subscription is also attached to matching signals that are registered after the
+/// subscription was made.
+///
+/// The argument types of the registered signals are checked at runtime. Requesting a signal with the wrong
+/// argument type results in a [RegistryError::TypeMismatch] error. A pattern subscription only attaches to the
+/// matching signals whose argument type is the same as the subscription's.
+///
+/// A `SignalRegistry` is a handle to its signals. Cloning a registry results in two handles to the same signals.
+/// # Example
+/// ```
+/// use signals2::*;
+/// use signals2::registry::{SignalRegistry, RegistryError};
+///
+/// let registry = SignalRegistry::new();
+/// let saved = registry.register::<(String,)>("editor/buffer/saved").unwrap();
+///
+/// let subscription = registry.subscribe("editor/*/saved", |path, (name,): (String,)| {
+///     println!("{}: {}", path, name);
+/// }).unwrap();
+///
+/// let tab_saved = registry.register::<(String,)>("editor/tab/saved").unwrap();
+/// saved.emit("a.txt".to_string()); // prints "editor/buffer/saved: a.txt"
+/// tab_saved.emit("b.txt".to_string()); // prints "editor/tab/saved: b.txt"
+///
+/// assert!(matches!(registry.get::<(i32,)>("editor/buffer/saved"), Err(RegistryError::TypeMismatch { .. })));
+/// ```
+#[derive(Clone, Default)]
+pub struct SignalRegistry {
+    state: Arc<RwLock<RegistryState>>
+}
+
+impl SignalRegistry {
+    /// Creates an empty registry.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registers a new signal under `path` and returns it. If a signal with the same argument type is already
+    /// registered under `path` that signal is returned instead. Matching pattern subscriptions are attached to
+    /// the newly registered signal.
+    pub fn register<Args>(&self, path: &str) -> Result<Signal<Args>, RegistryError>
+    where
+        Args: Clone + Send + Sync + 'static
+    {
+        validate_path(path)?;
+        let mut state = self.state.write().unwrap();
+        if let Some(entry) = state.signals.get(path) {
+            return entry.downcast(path);
+        }
+
+        let signal: Signal<Args> = Signal::new();
+        let entry = Entry {
+            type_id: TypeId::of::<Args>(),
+            type_name: std::any::type_name::<Args>(),
+            signal: Box::new(signal.clone())
+        };
+
+        state.subscribers.retain(|weak| weak.strong_count() > 0);
+        for subscriber in state.subscribers.iter().filter_map(Weak::upgrade) {
+            subscriber.attach(path, &entry);
+        }
+
+        state.signals.insert(path.to_string(), entry);
+        Ok(signal)
+    }
+
+    /// Returns the signal registered under `path`.
+    pub fn get<Args>(&self, path: &str) -> Result<Signal<Args>, RegistryError>
+    where
+        Args: Clone + Send + Sync + 'static
+    {
+        validate_path(path)?;
+        self.state
+            .read()
+            .unwrap()
+            .signals
+            .get(path)
+            .ok_or_else(|| RegistryError::NotFound(path.to_string()))
+            .and_then(|entry| entry.downcast(path))
+    }
+
+    /// Removes the signal registered under `path` from the registry. Returns false if no signal was registered
+    /// under `path`. The signal itself, and the slots connected to it, are unaffected.
+    pub fn unregister(&self, path: &str) -> bool {
+        self.state.write().unwrap().signals.remove(path).is_some()
+    }
+
+    /// Returns true if a signal is registered under `path`.
+    pub fn contains(&self, path: &str) -> bool {
+        self.state.read().unwrap().signals.contains_key(path)
+    }
+
+    /// Returns the paths of all registered signals that match `pattern`, in sorted order.
+    pub fn paths(&self, pattern: &str) -> Result<Vec<String>, RegistryError> {
+        let pattern = Pattern::parse(pattern)?;
+        let mut paths: Vec<String> = self.state
+            .read()
+            .unwrap()
+            .signals
+            .keys()
+            .filter(|path| pattern.matches(path))
+            .cloned()
+            .collect();
+
+        paths.sort();
+        Ok(paths)
+    }
+
+    /// Connects `f` to every signal with arguments of type `Args` whose path matches `pattern`, including signals
+    /// that are registered later. `f` is called with the path of the emitted signal and its arguments.
+    /// The slots are disconnected when the returned [Subscription] is dropped.
+    #[must_use = "the subscription is removed when the Subscription is dropped"]
+    pub fn subscribe<Args, F>(&self, pattern: &str, f: F) -> Result<Subscription, RegistryError>
+    where
+        Args: Clone + Send + Sync + 'static,
+        F: Fn(&str, Args) + Send + Sync + 'static
+    {
+        let subscriber: Arc<dyn Subscriber> = Arc::new(PatternSubscriber {
+            pattern: Pattern::parse(pattern)?,
+            f: Arc::new(f),
+            connections: Mutex::new(Vec::new()),
+            _args: std::marker::PhantomData
+        });
+
+        let mut state = self.state.write().unwrap();
+        for (path, entry) in state.signals.iter() {
+            subscriber.attach(path, entry);
+        }
+
+        state.subscribers.push(Arc::downgrade(&subscriber));
+        Ok(Subscription {
+            _subscriber: subscriber
+        })
+    }
+}
+
+/// A pattern subscription created by [SignalRegistry::subscribe]. The subscription, and all of the slots it
+/// connected, are removed when it is dropped.
+pub struct Subscription {
+    _subscriber: Arc<dyn Subscriber>
+}
+
+impl Subscription {
+    /// Removes the subscription. Equivalent to dropping it.
+    pub fn unsubscribe(self) {}
+}
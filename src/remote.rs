@@ -0,0 +1,373 @@
+// Copyright Christian Daley 2021
+// Copyright Frank Mori Hess 2007-2008.
+// Distributed under the Boost Software License, Version 1.0. 
+// See http://www.boost.org/LICENSE_1_0.txt
+
+use std::io::{self, BufReader};
+use std::net::{Shutdown, TcpStream, ToSocketAddrs};
+use std::sync::{Arc, Mutex, mpsc::{self, Sender, Receiver}, atomic::{AtomicBool, Ordering}};
+use std::thread::{self, JoinHandle};
+
+use serde::{Serialize, de::DeserializeOwned};
+
+use crate::{Signal, ConnectHandle, Group, Position, ScopedConnection, Emit};
+use crate::combiner::{Combiner, DefaultCombiner};
+use crate::connect::{Connect, Connection, Connect0, Connect1, Connect2, Connect3, Connect4, Connect5, Connect6, Connect7,
+    Connect8, Connect9, Connect10, Connect11, Connect12};
+use crate::record::{read_frame, write_frame};
+use crate::context::EmissionContext;
+
+/// A bidirectional channel that carries frames of bytes between the two ends of a remote signal. [RemotePublisher]
+/// sends frames and [RemoteSignal] receives them. Both methods may be called concurrently from different threads.
+pub trait Transport: Send + Sync {
+    /// Sends a single frame to the other end of the transport.
+    fn send_frame(&self, frame: &[u8]) -> io::Result<()>;
+
+    /// Blocks until a frame is received from the other end of the transport. Returns `None` once the transport has
+    /// been closed by either end.
+    fn recv_frame(&self) -> io::Result<Option<Vec<u8>>>;
+
+    /// Closes the transport. Any thread blocked in `recv_frame` on either end returns `None`, and subsequent calls to
+    /// `send_frame` fail.
+    fn close(&self);
+}
+
+/// An in-memory [Transport] between two endpoints in the same process, intended for tests. Dropping either endpoint
+/// closes the transport.
+pub struct MemoryTransport {
+    sender: Mutex<Sender<Option<Vec<u8>>>>,
+    receiver: Mutex<Receiver<Option<Vec<u8>>>>,
+    // a sender to this endpoint's own receiver, used to wake it when the transport is closed
+    waker: Mutex<Sender<Option<Vec<u8>>>>,
+    closed: AtomicBool
+}
+
+impl MemoryTransport {
+    /// Creates two connected endpoints. Frames sent on one endpoint are received by the other.
+    pub fn pair() -> (MemoryTransport, MemoryTransport) {
+        let (sender1, receiver1) = mpsc::channel();
+        let (sender2, receiver2) = mpsc::channel();
+
+        let end1 = MemoryTransport {
+            sender: Mutex::new(sender2.clone()),
+            receiver: Mutex::new(receiver1),
+            waker: Mutex::new(sender1.clone()),
+            closed: AtomicBool::new(false)
+        };
+
+        let end2 = MemoryTransport {
+            sender: Mutex::new(sender1),
+            receiver: Mutex::new(receiver2),
+            waker: Mutex::new(sender2),
+            closed: AtomicBool::new(false)
+        };
+
+        (end1, end2)
+    }
+}
+
+impl Transport for MemoryTransport {
+    fn send_frame(&self, frame: &[u8]) -> io::Result<()> {
+        if self.closed.load(Ordering::SeqCst) {
+            return Err(io::ErrorKind::BrokenPipe.into());
+        }
+
+        self.sender
+            .lock()
+            .unwrap()
+            .send(Some(frame.to_vec()))
+            .map_err(|_| io::ErrorKind::BrokenPipe.into())
+    }
+
+    fn recv_frame(&self) -> io::Result<Option<Vec<u8>>> {
+        if self.closed.load(Ordering::SeqCst) {
+            return Ok(None);
+        }
+
+        match self.receiver.lock().unwrap().recv() {
+            Ok(Some(frame)) => Ok(Some(frame)),
+            _ => {
+                self.closed.store(true, Ordering::SeqCst);
+                Ok(None)
+            }
+        }
+    }
+
+    fn close(&self) {
+        if !self.closed.swap(true, Ordering::SeqCst) {
+            let _ = self.sender.lock().unwrap().send(None);
+            let _ = self.waker.lock().unwrap().send(None);
+        }
+    }
+}
+
+impl Drop for MemoryTransport {
+    fn drop(&mut self) {
+        self.close();
+    }
+}
+
+/// A [Transport] over a TCP connection. Every frame is prefixed with its length as a little-endian `u32`. Frames larger
+/// than [MAX_FRAME_SIZE](crate::record::MAX_FRAME_SIZE) cannot be sent, and receiving a larger length prefix fails
+/// with [io::ErrorKind::InvalidData].
+pub struct TcpTransport {
+    writer: Mutex<TcpStream>,
+    reader: Mutex<BufReader<TcpStream>>
+}
+
+impl TcpTransport {
+    /// Connects to a [TcpTransport] listening at `addr`.
+    pub fn connect<A: ToSocketAddrs>(addr: A) -> io::Result<Self> {
+        Self::new(TcpStream::connect(addr)?)
+    }
+
+    /// Creates a transport from an established connection, such as one returned by
+    /// [TcpListener::accept](std::net::TcpListener::accept).
+    pub fn new(stream: TcpStream) -> io::Result<Self> {
+        stream.set_nodelay(true)?;
+        Ok(TcpTransport {
+            reader: Mutex::new(BufReader::new(stream.try_clone()?)),
+            writer: Mutex::new(stream)
+        })
+    }
+}
+
+impl Transport for TcpTransport {
+    fn send_frame(&self, frame: &[u8]) -> io::Result<()> {
+        write_frame(&mut *self.writer.lock().unwrap(), frame)
+    }
+
+    fn recv_frame(&self) -> io::Result<Option<Vec<u8>>> {
+        read_frame(&mut *self.reader.lock().unwrap())
+    }
+
+    fn close(&self) {
+        let _ = self.writer.lock().unwrap().shutdown(Shutdown::Both);
+    }
+}
+
+/// Sends the emissions of a local signal over a [Transport] to a [RemoteSignal]. Requires the `serde` feature.
+///
+/// The publisher is connected to the signal at the back of [Group::Back] and does not contribute to the signal's
+/// return value. Every emission is encoded with bincode and sent on the emitting thread. If sending fails the publisher
+/// disconnects itself from the signal. Dropping the publisher disconnects it and closes the transport.
+pub struct RemotePublisher {
+    transport: Arc<dyn Transport>,
+    conn: ScopedConnection
+}
+
+impl RemotePublisher {
+    /// Creates a publisher that sends the emissions of `sig` over `transport`.
+    pub fn new<Args, R, C, G, T>(sig: &Signal<Args, R, C, G>, transport: T) -> Self
+    where
+        Args: Serialize + Clone + 'static,
+        R: 'static,
+        C: Combiner<R> + 'static,
+        G: Ord + Send + Sync + 'static,
+        T: Transport + 'static
+    {
+        let transport: Arc<dyn Transport> = Arc::new(transport);
+        let slot_transport = transport.clone();
+        let conn = sig.connect_partial(move |conn, args| {
+            let sent = bincode::serialize(&args)
+                .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))
+                .and_then(|frame| slot_transport.send_frame(&frame));
+
+            if sent.is_err() {
+                conn.disconnect();
+            }
+
+            None
+        }, Group::Back, Position::Back).scoped();
+
+        RemotePublisher {
+            transport,
+            conn
+        }
+    }
+
+    /// Returns `true` if the publisher is still sending emissions.
+    pub fn connected(&self) -> bool {
+        self.conn.connected()
+    }
+}
+
+impl Drop for RemotePublisher {
+    fn drop(&mut self) {
+        self.transport.close();
+    }
+}
+
+/// A signal whose emissions come from a [RemotePublisher] at the other end of a [Transport]. A `RemoteSignal`
+/// implements the same [connect](crate::connect) traits as a [Signal], so code that consumes events does not need to
+/// know whether they are produced locally or remotely. Requires the `serde` feature.
+///
+/// Frames are received and decoded on a background thread, which also executes the slots. Frames that cannot be
+/// decoded are skipped. Dropping the `RemoteSignal` closes the transport.
+/// # Example
+/// ```
+/// use signals2::*;
+/// use signals2::remote::{MemoryTransport, RemotePublisher, RemoteSignal};
+/// use std::time::Duration;
+///
+/// let (publisher_end, signal_end) = MemoryTransport::pair();
+/// let local: Signal<(i32, i32)> = Signal::new();
+/// let _publisher = RemotePublisher::new(&local, publisher_end);
+///
+/// let remote: RemoteSignal<(i32, i32)> = RemoteSignal::new(signal_end);
+/// let (sender, receiver) = std::sync::mpsc::channel();
+/// let sender = std::sync::Mutex::new(sender);
+/// remote.connect(move |x, y| sender.lock().unwrap().send(x + y).unwrap());
+///
+/// local.emit(1, 2);
+/// assert_eq!(receiver.recv_timeout(Duration::from_secs(5)), Ok(3));
+/// ```
+pub struct RemoteSignal<Args, R = (), C = DefaultCombiner, G = i32>
+where
+    Args: Clone + 'static,
+    R: 'static,
+    C: Combiner<R> + 'static,
+    G: Ord + Send + Sync + 'static
+{
+    sig: Signal<Args, R, C, G>,
+    transport: Arc<dyn Transport>,
+    thread: Option<JoinHandle<()>>
+}
+
+impl<Args, R, C, G> RemoteSignal<Args, R, C, G>
+where
+    Args: DeserializeOwned + Clone + 'static,
+    R: 'static,
+    C: Combiner<R> + 'static,
+    G: Ord + Send + Sync + 'static
+{
+    /// Creates a remote signal that receives emissions over `transport`.
+    pub fn new<T>(transport: T) -> Self
+    where
+        C: Default,
+        T: Transport + 'static
+    {
+        Self::new_with_combiner(transport, C::default())
+    }
+
+    /// Creates a remote signal with the given combiner that receives emissions over `transport`.
+    pub fn new_with_combiner<T>(transport: T, combiner: C) -> Self
+    where
+        T: Transport + 'static
+    {
+        let sig = Signal::new_with_combiner(combiner);
+        let transport: Arc<dyn Transport> = Arc::new(transport);
+        let thread_transport = transport.clone();
+        let emit_handle = sig.get_emit_handle();
+        let thread = thread::spawn(move || {
+            while let Ok(Some(frame)) = thread_transport.recv_frame() {
+                if let Ok(args) = bincode::deserialize::<Args>(&frame) {
+                    if emit_handle.emit_tuple(args).is_none() {
+                        break;
+                    }
+                }
+            }
+        });
+
+        RemoteSignal {
+            sig,
+            transport,
+            thread: Some(thread)
+        }
+    }
+
+    /// Returns a [ConnectHandle] to the underlying signal.
+    pub fn get_connect_handle(&self) -> ConnectHandle<Args, R, C, G> {
+        self.sig.get_connect_handle()
+    }
+
+    /// Returns the number of slots connected to the signal.
+    pub fn count(&self) -> usize {
+        self.sig.count()
+    }
+
+    /// Returns `true` while the transport is receiving emissions.
+    pub fn is_open(&self) -> bool {
+        self.thread.as_ref().map_or(false, |thread| !thread.is_finished())
+    }
+}
+
+impl<Args, R, C, G> Drop for RemoteSignal<Args, R, C, G>
+where
+    Args: Clone + 'static,
+    R: 'static,
+    C: Combiner<R> + 'static,
+    G: Ord + Send + Sync + 'static
+{
+    fn drop(&mut self) {
+        self.transport.close();
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+impl<Args, R, C, G> Connect<Args> for RemoteSignal<Args, R, C, G>
+where
+    Args: Clone + 'static,
+    R: 'static,
+    C: Combiner<R> + 'static,
+    G: Ord + Send + Sync + 'static
+{
+    type Return = R;
+    type Group = G;
+
+    fn connect_tuple_group_position<F>(&self, f: F, group: Group<G>, pos: Position) -> Connection
+    where
+        F: Fn(Args) -> R + Send + Sync + 'static
+    {
+        self.sig.connect_tuple_group_position(f, group, pos)
+    }
+
+    fn connect_tuple_group_position_extended<F>(&self, f: F, group: Group<G>, pos: Position) -> Connection
+    where
+        F: Fn(Connection, Args) -> R + Send + Sync + 'static
+    {
+        self.sig.connect_tuple_group_position_extended(f, group, pos)
+    }
+
+    fn connect_tuple_group_position_with_context<F>(&self, f: F, group: Group<G>, pos: Position) -> Connection
+    where
+        F: Fn(&EmissionContext<G>, Args) -> R + Send + Sync + 'static
+    {
+        self.sig.connect_tuple_group_position_with_context(f, group, pos)
+    }
+
+    fn connect_tuple_group_position_partial<F>(&self, f: F, group: Group<G>, pos: Position) -> Connection
+    where
+        F: Fn(&Connection, Args) -> Option<R> + Send + Sync + 'static
+    {
+        self.sig.connect_tuple_group_position_partial(f, group, pos)
+    }
+}
+
+macro_rules! impl_remote {
+    ($connect:ident; $($args:ident)*) => {
+        impl<R, C, G, $($args,)*> $connect<R, C, G, $($args,)*> for RemoteSignal<($($args,)*), R, C, G>
+        where
+            ($($args,)*): Clone + 'static,
+            R: 'static,
+            C: Combiner<R> + 'static,
+            G: Ord + Send + Sync + 'static
+        {}
+    };
+}
+
+impl_remote!(Connect0;);
+impl_remote!(Connect1; T0);
+impl_remote!(Connect2; T0 T1);
+impl_remote!(Connect3; T0 T1 T2);
+impl_remote!(Connect4; T0 T1 T2 T3);
+impl_remote!(Connect5; T0 T1 T2 T3 T4);
+impl_remote!(Connect6; T0 T1 T2 T3 T4 T5);
+impl_remote!(Connect7; T0 T1 T2 T3 T4 T5 T6);
+impl_remote!(Connect8; T0 T1 T2 T3 T4 T5 T6 T7);
+impl_remote!(Connect9; T0 T1 T2 T3 T4 T5 T6 T7 T8);
+impl_remote!(Connect10; T0 T1 T2 T3 T4 T5 T6 T7 T8 T9);
+impl_remote!(Connect11; T0 T1 T2 T3 T4 T5 T6 T7 T8 T9 T10);
+impl_remote!(Connect12; T0 T1 T2 T3 T4 T5 T6 T7 T8 T9 T10 T11);
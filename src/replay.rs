@@ -0,0 +1,542 @@
+// Copyright Christian Daley 2021
+// Copyright Frank Mori Hess 2007-2008.
+// Distributed under the Boost Software License, Version 1.0. 
+// See http://www.boost.org/LICENSE_1_0.txt
+
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+use std::thread::{self, ThreadId};
+
+use crate::{Signal, Connection, Group, Position};
+use crate::signal_core::SignalCore;
+use crate::combiner::{Combiner, DefaultCombiner};
+use crate::connect::{Connect, Connect0, Connect1, Connect2, Connect3, Connect4, Connect5, Connect6, Connect7, Connect8,
+    Connect9, Connect10, Connect11, Connect12};
+use crate::emit::{Emit, Emit0, Emit1, Emit2, Emit3, Emit4, Emit5, Emit6, Emit7, Emit8, Emit9, Emit10, Emit11, Emit12};
+use crate::context::EmissionContext;
+
+// The most recent emissions of a signal, oldest first.
+struct History<Args> {
+    values: VecDeque<Args>,
+    capacity: usize
+}
+
+impl<Args> History<Args> {
+    fn new(capacity: usize) -> Self {
+        History {
+            values: VecDeque::with_capacity(capacity),
+            capacity
+        }
+    }
+
+    fn record(&mut self, args: Args) {
+        if self.capacity == 0 {
+            return;
+        }
+
+        if self.values.len() >= self.capacity {
+            self.values.pop_front();
+        }
+
+        self.values.push_back(args);
+    }
+}
+
+struct GateState<Args> {
+    open: bool,
+    replayer: Option<ThreadId>,
+    pending: VecDeque<Args>
+}
+
+// Holds back live emissions to a newly connected slot until the recorded history has been replayed into it. While
+// the gate is closed, only the thread that is replaying may execute the slot, so the slot can still emit the signal
+// from within a replayed execution. Live emissions from other threads are buffered and delivered in order by the
+// replaying thread once the replay has finished.
+struct ReplayGate<Args> {
+    state: Mutex<GateState<Args>>
+}
+
+impl<Args> ReplayGate<Args> {
+    fn new() -> Self {
+        ReplayGate {
+            state: Mutex::new(GateState {
+                open: false,
+                replayer: None,
+                pending: VecDeque::new()
+            })
+        }
+    }
+
+    // Returns the arguments if the slot may be executed with them now, or buffers them and returns `None`.
+    fn pass(&self, args: Args) -> Option<Args> {
+        let mut state = self.state.lock().unwrap();
+        if state.open || state.replayer == Some(thread::current().id()) {
+            Some(args)
+        } else {
+            state.pending.push_back(args);
+            None
+        }
+    }
+
+    // Executes the slot with the given ID as a separate emission of the signal, letting it through the gate.
+    fn deliver<R, C, G>(&self, core: &SignalCore<Args, R, C, G>, id: u64, args: Args)
+    where
+        Args: Clone + 'static,
+        R: 'static,
+        C: Combiner<R> + 'static,
+        G: Ord + Send + Sync + 'static
+    {
+        self.state.lock().unwrap().replayer = Some(thread::current().id());
+        let _close = CloseOnDrop(self);
+        core.emit_slot(id, args);
+    }
+
+    // Delivers the live emissions that were buffered during the replay, then lets all further emissions through.
+    fn open<R, C, G>(&self, core: &SignalCore<Args, R, C, G>, id: u64)
+    where
+        Args: Clone + 'static,
+        R: 'static,
+        C: Combiner<R> + 'static,
+        G: Ord + Send + Sync + 'static
+    {
+        loop {
+            let mut state = self.state.lock().unwrap();
+            match state.pending.pop_front() {
+                Some(args) => {
+                    std::mem::drop(state);
+                    self.deliver(core, id, args);
+                },
+                None => {
+                    state.open = true;
+                    return;
+                }
+            }
+        }
+    }
+}
+
+// Clears the replaying thread once a replayed execution has finished, even if the slot panicked.
+struct CloseOnDrop<'a, Args>(&'a ReplayGate<Args>);
+
+impl<'a, Args> Drop for CloseOnDrop<'a, Args> {
+    fn drop(&mut self) {
+        if let Ok(mut state) = self.0.state.lock() {
+            state.replayer = None;
+        }
+    }
+}
+
+// Opens the gate without delivering anything if the replay never gets to run, for example because the signal was
+// dropped while the replay was queued, so that the slot is not shut out of later emissions.
+struct OpenOnDrop<Args>(Arc<ReplayGate<Args>>);
+
+impl<Args> Drop for OpenOnDrop<Args> {
+    fn drop(&mut self) {
+        if let Ok(mut state) = self.0.state.lock() {
+            state.open = true;
+            state.pending.clear();
+        }
+    }
+}
+
+// Connects a slot with `connect` and replays the recorded history into it. The slot is connected and the history
+// copied while holding the history lock, and `emit_and_record` takes its snapshot of the slots while holding the same
+// lock, so a newly connected slot never misses an emission and never sees one twice. The slot is connected behind a
+// closed `ReplayGate`, so emissions on other threads that reach it before the replay has finished are delivered after
+// the replayed ones rather than interleaved with them.
+//
+// The lock is released before replaying, so the slot may connect to or emit the signal while it is being replayed.
+// The replay runs as an emission of the signal: it is subject to its reentrancy policy and maximum depth, and each
+// replayed execution is skipped while the signal or the slot is blocked.
+fn connect_and_replay<Args, R, C, G>(history: &Mutex<History<Args>>, signal: &Signal<Args, R, C, G>, connect: impl FnOnce(Arc<ReplayGate<Args>>) -> Connection) -> Connection
+where
+    Args: Clone + 'static,
+    R: 'static,
+    C: Combiner<R> + 'static,
+    G: Ord + Send + Sync + 'static
+{
+    let gate = Arc::new(ReplayGate::new());
+    let lock = history.lock().unwrap();
+    let conn = connect(gate.clone());
+    let values: Vec<Args> = lock.values.iter().cloned().collect();
+    std::mem::drop(lock);
+
+    let weak = signal.weak();
+    let id = conn.id();
+    let guard = OpenOnDrop(gate);
+    signal.snapshot().replay(move |run| {
+        if let Some(signal) = weak.upgrade() {
+            if run {
+                for args in values {
+                    guard.0.deliver(&signal.snapshot(), id, args);
+                }
+            }
+
+            guard.0.open(&signal.snapshot(), id);
+        }
+    });
+
+    conn
+}
+
+fn emit_and_record<Args, R, C, G>(history: &Mutex<History<Args>>, signal: &Signal<Args, R, C, G>, args: Args, emit: impl FnOnce(&Arc<SignalCore<Args, R, C, G>>, &Args) -> C::Output) -> C::Output
+where
+    Args: Clone + 'static,
+    R: 'static,
+    C: Combiner<R> + 'static,
+    G: Ord + Send + Sync + 'static
+{
+    let mut history = history.lock().unwrap();
+    history.record(args.clone());
+    let core = signal.snapshot();
+    std::mem::drop(history);
+    emit(&core, &args)
+}
+
+/// A signal that remembers the arguments it was most recently emitted with. Every newly connected slot
+/// is immediately executed with those arguments, so that it starts out with the current "state" rather than
+/// waiting for the next emission. Useful for state-like events such as "connection status changed".
+///
+/// A `BehaviorSignal` supports the same [connect](crate::connect) and [emit](crate::emit) traits as a
+/// [Signal], and slots are ordered by [Group] and [Position] in the same way. The return value of the
+/// replayed execution at connect time is discarded. Emissions on other threads that reach a slot while it is still
+/// being replayed are held back and delivered to it after the replay, which is why the arguments must be `Send`.
+/// # Example
+/// ```
+/// use signals2::*;
+/// use signals2::replay::BehaviorSignal;
+///
+/// let sig: BehaviorSignal<(bool,), bool> = BehaviorSignal::with_value((false,));
+/// sig.emit(true);
+/// sig.connect(|connected| {
+///     println!("connected: {}", connected); // prints "connected: true" immediately
+///     connected
+/// });
+/// assert_eq!(sig.value(), Some((true,)));
+/// assert_eq!(sig.emit(false), Some(false));
+/// ```
+pub struct BehaviorSignal<Args, R = (), C = DefaultCombiner, G = i32>
+where
+    Args: Clone + 'static,
+    R: 'static,
+    C: Combiner<R> + 'static,
+    G: Ord + Send + Sync + 'static
+{
+    signal: Signal<Args, R, C, G>,
+    history: Arc<Mutex<History<Args>>>
+}
+
+/// A signal that remembers the arguments of its last `n` emissions. Every newly connected slot is immediately
+/// executed once for each remembered emission, oldest first.
+///
+/// Like a [BehaviorSignal], a `ReplaySignal` supports the same [connect](crate::connect) and
+/// [emit](crate::emit) traits as a [Signal]. The return values of the replayed executions are discarded.
+/// # Example
+/// ```
+/// use signals2::*;
+/// use signals2::replay::ReplaySignal;
+///
+/// let sig: ReplaySignal<(i32,)> = ReplaySignal::new(2);
+/// sig.emit(1);
+/// sig.emit(2);
+/// sig.emit(3);
+/// sig.connect(|x| println!("{}", x)); // prints "2" and then "3"
+/// assert_eq!(sig.history(), vec!((2,), (3,)));
+/// ```
+pub struct ReplaySignal<Args, R = (), C = DefaultCombiner, G = i32>
+where
+    Args: Clone + 'static,
+    R: 'static,
+    C: Combiner<R> + 'static,
+    G: Ord + Send + Sync + 'static
+{
+    signal: Signal<Args, R, C, G>,
+    history: Arc<Mutex<History<Args>>>
+}
+
+macro_rules! impl_common {
+    ($name:ident) => {
+        impl<Args, R, C, G> Clone for $name<Args, R, C, G>
+        where
+            Args: Clone + 'static,
+            R: 'static,
+            C: Combiner<R> + 'static,
+            G: Ord + Send + Sync + 'static
+        {
+            /// Clones the signal. Both handles share the same slots and the same remembered emissions.
+            fn clone(&self) -> Self {
+                Self {
+                    signal: self.signal.clone(),
+                    history: self.history.clone()
+                }
+            }
+        }
+
+        impl<Args, R, C, G> $name<Args, R, C, G>
+        where
+            Args: Clone + 'static,
+            R: 'static,
+            C: Combiner<R> + 'static,
+            G: Ord + Send + Sync + 'static
+        {
+            /// Sets a new [Combiner] for the signal.
+            pub fn set_combiner(&self, combiner: C) {
+                self.signal.set_combiner(combiner);
+            }
+
+            /// Disconnects all slots from the signal. The remembered emissions are kept.
+            pub fn clear(&self) {
+                self.signal.clear();
+            }
+
+            /// Returns the number of connected slots for the signal.
+            pub fn count(&self) -> usize {
+                self.signal.count()
+            }
+
+            /// Forgets all remembered emissions. Slots connected afterwards are not executed until the
+            /// signal is emitted again.
+            pub fn reset(&self) {
+                self.history.lock().unwrap().values.clear();
+            }
+        }
+    };
+}
+
+impl_common!(BehaviorSignal);
+impl_common!(ReplaySignal);
+
+impl<Args, R, C, G> BehaviorSignal<Args, R, C, G>
+where
+    Args: Clone + 'static,
+    R: 'static,
+    C: Combiner<R> + 'static,
+    G: Ord + Send + Sync + 'static
+{
+    /// Creates a new behavior signal with a corresponding [Combiner]. If `value` is `Some(args)`, slots
+    /// connected before the first emission are executed with `args`.
+    pub fn new_with_combiner(combiner: C, value: Option<Args>) -> Self {
+        let mut history = History::new(1);
+        if let Some(args) = value {
+            history.record(args);
+        }
+
+        BehaviorSignal {
+            signal: Signal::new_with_combiner(combiner),
+            history: Arc::new(Mutex::new(history))
+        }
+    }
+
+    /// Returns the arguments the signal was most recently emitted with, or its initial value if it has not
+    /// been emitted yet.
+    pub fn value(&self) -> Option<Args> {
+        self.history.lock().unwrap().values.back().cloned()
+    }
+}
+
+impl<Args, R, C, G> BehaviorSignal<Args, R, C, G>
+where
+    Args: Clone + 'static,
+    R: 'static,
+    C: Combiner<R> + Default + 'static,
+    G: Ord + Send + Sync + 'static
+{
+    /// Creates a new behavior signal with no initial value. Slots connected before the first emission
+    /// are not executed until the signal is emitted.
+    pub fn new() -> Self {
+        Self::new_with_combiner(C::default(), None)
+    }
+
+    /// Creates a new behavior signal with an initial value that slots connected before the first emission
+    /// are executed with.
+    pub fn with_value(value: Args) -> Self {
+        Self::new_with_combiner(C::default(), Some(value))
+    }
+}
+
+impl<Args, R, C, G> Default for BehaviorSignal<Args, R, C, G>
+where
+    Args: Clone + 'static,
+    R: 'static,
+    C: Combiner<R> + Default + 'static,
+    G: Ord + Send + Sync + 'static
+{
+    /// Equivalent to calling `BehaviorSignal::new()`.
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<Args, R, C, G> ReplaySignal<Args, R, C, G>
+where
+    Args: Clone + 'static,
+    R: 'static,
+    C: Combiner<R> + 'static,
+    G: Ord + Send + Sync + 'static
+{
+    /// Creates a new replay signal that remembers the last `capacity` emissions, with a corresponding [Combiner].
+    pub fn new_with_combiner(capacity: usize, combiner: C) -> Self {
+        ReplaySignal {
+            signal: Signal::new_with_combiner(combiner),
+            history: Arc::new(Mutex::new(History::new(capacity)))
+        }
+    }
+
+    /// Returns the remembered emissions, oldest first.
+    pub fn history(&self) -> Vec<Args> {
+        self.history.lock().unwrap().values.iter().cloned().collect()
+    }
+
+    /// Returns the maximum number of emissions the signal remembers.
+    pub fn capacity(&self) -> usize {
+        self.history.lock().unwrap().capacity
+    }
+}
+
+impl<Args, R, C, G> ReplaySignal<Args, R, C, G>
+where
+    Args: Clone + 'static,
+    R: 'static,
+    C: Combiner<R> + Default + 'static,
+    G: Ord + Send + Sync + 'static
+{
+    /// Creates a new replay signal that remembers the last `capacity` emissions.
+    pub fn new(capacity: usize) -> Self {
+        Self::new_with_combiner(capacity, C::default())
+    }
+}
+
+macro_rules! impl_replay_traits {
+    ($name:ident) => {
+        impl<Args, R, C, G> Connect<Args> for $name<Args, R, C, G>
+        where
+            Args: Clone + Send + 'static,
+            R: 'static,
+            C: Combiner<R> + 'static,
+            G: Ord + Send + Sync + 'static
+        {
+            type Return = R;
+            type Group = G;
+
+            fn connect_tuple_group_position<F>(&self, f: F, group: Group<G>, pos: Position) -> Connection
+            where
+                F: Fn(Args) -> R + Send + Sync + 'static
+            {
+                connect_and_replay(&self.history, &self.signal, |gate| {
+                    self.signal.connect_partial(move |_, args| gate.pass(args).map(&f), group, pos)
+                })
+            }
+
+            fn connect_tuple_group_position_extended<F>(&self, f: F, group: Group<G>, pos: Position) -> Connection
+            where
+                F: Fn(Connection, Args) -> R + Send + Sync + 'static
+            {
+                connect_and_replay(&self.history, &self.signal, |gate| {
+                    self.signal.connect_partial(move |conn, args| gate.pass(args).map(|args| f(conn.clone(), args)), group, pos)
+                })
+            }
+
+            fn connect_tuple_group_position_with_context<F>(&self, f: F, group: Group<G>, pos: Position) -> Connection
+            where
+                F: Fn(&EmissionContext<G>, Args) -> R + Send + Sync + 'static
+            {
+                connect_and_replay(&self.history, &self.signal, |gate| {
+                    self.signal.connect_partial_with_context(move |ctx, args| gate.pass(args).map(|args| f(ctx, args)), group, pos)
+                })
+            }
+
+            fn connect_tuple_group_position_partial<F>(&self, f: F, group: Group<G>, pos: Position) -> Connection
+            where
+                F: Fn(&Connection, Args) -> Option<R> + Send + Sync + 'static
+            {
+                connect_and_replay(&self.history, &self.signal, |gate| {
+                    self.signal.connect_partial(move |conn, args| gate.pass(args).and_then(|args| f(conn, args)), group, pos)
+                })
+            }
+        }
+
+        impl<Args, R, C, G> Emit<Args> for $name<Args, R, C, G>
+        where
+            Args: Clone + 'static,
+            R: 'static,
+            C: Combiner<R> + 'static,
+            G: Ord + Send + Sync + 'static
+        {
+            type Return = R;
+            type Output = C::Output;
+
+            fn emit_tuple(&self, args: Args) -> C::Output {
+                emit_and_record(&self.history, &self.signal, args, |core, args| core.emit(args))
+            }
+
+            fn emit_tuple_parallel(&self, args: Args) -> C::Output
+            where
+                Args: Send + Sync,
+                R: Send
+            {
+                emit_and_record(&self.history, &self.signal, args, |core, args| core.emit_parallel(args, false))
+            }
+
+            fn emit_tuple_parallel_grouped(&self, args: Args) -> C::Output
+            where
+                Args: Send + Sync,
+                R: Send
+            {
+                emit_and_record(&self.history, &self.signal, args, |core, args| core.emit_parallel(args, true))
+            }
+        }
+    };
+}
+
+impl_replay_traits!(BehaviorSignal);
+impl_replay_traits!(ReplaySignal);
+
+macro_rules! impl_replay {
+    ($connect:ident; $emit:ident; $($args:ident)*) => {
+        impl<R, C, G, $($args,)*> $connect<R, C, G, $($args,)*> for BehaviorSignal<($($args,)*), R, C, G>
+        where
+            ($($args,)*): Clone + Send + 'static,
+            R: 'static,
+            C: Combiner<R> + 'static,
+            G: Ord + Send + Sync + 'static
+        {}
+
+        impl<R, C, G, $($args,)*> $connect<R, C, G, $($args,)*> for ReplaySignal<($($args,)*), R, C, G>
+        where
+            ($($args,)*): Clone + Send + 'static,
+            R: 'static,
+            C: Combiner<R> + 'static,
+            G: Ord + Send + Sync + 'static
+        {}
+
+        impl<R, C, G, $($args,)*> $emit<R, C, $($args,)*> for BehaviorSignal<($($args,)*), R, C, G>
+        where
+            ($($args,)*): Clone + 'static,
+            R: 'static,
+            C: Combiner<R> + 'static,
+            G: Ord + Send + Sync + 'static
+        {}
+
+        impl<R, C, G, $($args,)*> $emit<R, C, $($args,)*> for ReplaySignal<($($args,)*), R, C, G>
+        where
+            ($($args,)*): Clone + 'static,
+            R: 'static,
+            C: Combiner<R> + 'static,
+            G: Ord + Send + Sync + 'static
+        {}
+    };
+}
+
+impl_replay!(Connect0; Emit0;);
+impl_replay!(Connect1; Emit1; T0);
+impl_replay!(Connect2; Emit2; T0 T1);
+impl_replay!(Connect3; Emit3; T0 T1 T2);
+impl_replay!(Connect4; Emit4; T0 T1 T2 T3);
+impl_replay!(Connect5; Emit5; T0 T1 T2 T3 T4);
+impl_replay!(Connect6; Emit6; T0 T1 T2 T3 T4 T5);
+impl_replay!(Connect7; Emit7; T0 T1 T2 T3 T4 T5 T6);
+impl_replay!(Connect8; Emit8; T0 T1 T2 T3 T4 T5 T6 T7);
+impl_replay!(Connect9; Emit9; T0 T1 T2 T3 T4 T5 T6 T7 T8);
+impl_replay!(Connect10; Emit10; T0 T1 T2 T3 T4 T5 T6 T7 T8 T9);
+impl_replay!(Connect11; Emit11; T0 T1 T2 T3 T4 T5 T6 T7 T8 T9 T10);
+impl_replay!(Connect12; Emit12; T0 T1 T2 T3 T4 T5 T6 T7 T8 T9 T10 T11);
@@ -3,12 +3,18 @@
 // Distributed under the Boost Software License, Version 1.0. 
 // See http://www.boost.org/LICENSE_1_0.txt
 
-use std::sync::{Arc, Weak, atomic::{AtomicUsize, AtomicIsize, AtomicBool, Ordering}};
-use std::collections::BTreeSet;
+use std::sync::{Arc, Weak, RwLock, atomic::{AtomicUsize, AtomicIsize, AtomicBool, AtomicU64, Ordering}};
+use std::collections::{BTreeSet, HashSet};
 use std::cmp;
+use std::cell::Cell;
+#[cfg(not(feature = "rayon"))]
+use std::{thread, panic};
 
 use crate::combiner::Combiner;
 use crate::connect::{Position, Group, Connection};
+use crate::untyped::{SlotInfo, SlotGroup};
+use crate::reentrancy::{self, ReentrancyConfig, ReentrancyPolicy, ReentrancyError, DepthGuard};
+use crate::context::{Emission, EmissionContext};
 
 fn next_position(pos: &Position) -> isize {
     static POSITION_COUNTER: AtomicIsize = AtomicIsize::new(0);
@@ -20,13 +26,29 @@ fn next_position(pos: &Position) -> isize {
     POSITION_COUNTER.fetch_add(1, Ordering::Relaxed) * sign
 }
 
+// Returns a new ID that is unique among all signals and slots. Zero is never returned.
+pub fn next_id() -> u64 {
+    static ID_COUNTER: AtomicU64 = AtomicU64::new(1);
+    ID_COUNTER.fetch_add(1, Ordering::Relaxed)
+}
+
 // A key used to indentify a slot. This tuple will implement Ord
 // because both Group<G> and isize implement Ord.
 type SlotKey<G> = (Group<G>, isize);
 
-enum SlotFunc<Args, R> {
+type PartialSlotFunc<Args, R> = Box<dyn Fn(&Connection, Args) -> Option<R> + Send + Sync + 'static>;
+type ContextualSlotFunc<Args, R, G> = Box<dyn Fn(&EmissionContext<G>, Args) -> Option<R> + Send + Sync + 'static>;
+
+enum SlotFunc<Args, R, G>
+where
+    G: Ord + Send + Sync
+{
     Basic(Box<dyn Fn(Args) -> R + Send + Sync + 'static>),
-    Extended((Box<dyn Fn(Connection, Args) -> R + Send + Sync + 'static>, Connection))
+    Extended((Box<dyn Fn(Connection, Args) -> R + Send + Sync + 'static>, Connection)),
+    // A slot that may decline to produce a value. When a partial slot returns None
+    // it is invisible to the combiner.
+    Partial((PartialSlotFunc<Args, R>, Connection)),
+    Contextual((ContextualSlotFunc<Args, R, G>, Connection))
 }
 
 struct Slot<Args, R, G> 
@@ -35,10 +57,11 @@ where
     R: 'static,
     G: Ord + Send + Sync + 'static
 {
-    func: SlotFunc<Args, R>,
+    func: SlotFunc<Args, R, G>,
     connected: Arc<AtomicBool>,
     blocker_count: Arc<AtomicUsize>,
-    key: SlotKey<G>
+    key: SlotKey<G>,
+    id: u64
 }
 
 impl<Args, R, G> PartialEq for Slot<Args, R, G> 
@@ -66,7 +89,7 @@ where
     G: Ord + Send + Sync + 'static
 {
     fn partial_cmp(&self, other: &Self) -> Option<cmp::Ordering> {
-        Some(self.cmp(&other))
+        Some(self.cmp(other))
     }
 }
 
@@ -87,10 +110,12 @@ where
     R: 'static,
     G: Ord + Send + Sync + 'static
 {
-    fn emit(&self, args: Args) -> R {
+    fn emit(&self, args: Args, emission: &Emission, index: usize) -> Option<R> {
         match &self.func {
-            SlotFunc::Basic(f) => f(args),
-            SlotFunc::Extended((f, conn)) => f(conn.clone(), args)
+            SlotFunc::Basic(f) => Some(f(args)),
+            SlotFunc::Extended((f, conn)) => Some(f(conn.clone(), args)),
+            SlotFunc::Partial((f, conn)) => f(conn, args),
+            SlotFunc::Contextual((f, conn)) => f(&EmissionContext::new(emission, &self.key.0, index, conn), args)
         }
     }
 
@@ -107,6 +132,10 @@ where
     }
 }
 
+// The lockable handle to a signal's core that all clones of a signal share.
+pub type SharedCore<Args, R, C, G> = Arc<RwLock<Arc<SignalCore<Args, R, C, G>>>>;
+pub type WeakCore<Args, R, C, G> = Weak<RwLock<Arc<SignalCore<Args, R, C, G>>>>;
+
 pub struct SignalCore<Args, R, C, G> 
 where 
     Args: Clone + 'static,
@@ -114,8 +143,18 @@ where
     C: Combiner<R> + 'static,
     G: Ord + Send + Sync + 'static
 {
+    id: u64,
     slots: BTreeSet<Arc<Slot<Args, R, G>>>,
-    combiner: Arc<C>
+    combiner: Arc<C>,
+    // Blocks every slot of the signal. Shared by all versions of the core.
+    blocker_count: Arc<AtomicUsize>,
+    reentrancy: ReentrancyConfig,
+    // The number of tracked emissions that have executed the signal's slots. Shared by all versions of the core.
+    emission_count: Arc<AtomicU64>,
+    // The number of slots that receive an emission context, including disconnected slots that are still stored.
+    contextual_slots: usize,
+    // The handle that owns every version of the core, used to reach the current version.
+    shared: WeakCore<Args, R, C, G>
 }
 
 impl<Args, R, C, G> Clone for SignalCore<Args, R, C, G> 
@@ -127,8 +166,14 @@ where
 {
     fn clone(&self) -> Self {
         Self {
+            id: self.id,
             slots: self.slots.clone(),
-            combiner: self.combiner.clone()
+            combiner: self.combiner.clone(),
+            blocker_count: self.blocker_count.clone(),
+            reentrancy: self.reentrancy.clone(),
+            emission_count: self.emission_count.clone(),
+            contextual_slots: self.contextual_slots,
+            shared: self.shared.clone()
         }
     }
 }
@@ -140,60 +185,264 @@ where
     C: Combiner<R> + 'static,
     G: Ord + Send + Sync + 'static
 {
-    pub fn new(combiner: C) -> Self {
+    pub fn new(combiner: C, shared: WeakCore<Args, R, C, G>) -> Self {
         SignalCore {
+            id: next_id(),
             slots: BTreeSet::new(),
-            combiner: Arc::new(combiner)
+            combiner: Arc::new(combiner),
+            blocker_count: Arc::new(AtomicUsize::new(0usize)),
+            reentrancy: ReentrancyConfig::default(),
+            emission_count: Arc::new(AtomicU64::new(0)),
+            contextual_slots: 0,
+            shared
         }
     }
 
-    pub fn emit(&self, args: &Args) -> C::Output {
-        let iter = self.slots.iter().filter_map(
-            |slot| {
-                if slot.connected() && !slot.blocked() {
-                    Some(slot.emit(args.clone()))
-                } else {
+    pub fn emit(self: &Arc<Self>, args: &Args) -> C::Output {
+        self.try_emit(args).unwrap_or_else(|_| self.emit_none())
+    }
+
+    pub fn try_emit(self: &Arc<Self>, args: &Args) -> Result<C::Output, ReentrancyError> {
+        if !self.tracked() {
+            return Ok(self.emit_slots(args, untracked_emission()));
+        }
+
+        self.guarded(|| self.queued_emission(args), |core| core.emit_slots(args, core.begin_emission()))
+    }
+
+    pub fn emit_parallel(self: &Arc<Self>, args: &Args, grouped: bool) -> C::Output
+    where
+        Args: Send + Sync,
+        R: Send
+    {
+        if !self.tracked() {
+            return self.emit_slots_parallel(args, grouped, untracked_emission());
+        }
+
+        self.guarded(|| self.queued_emission(args), |core| core.emit_slots_parallel(args, grouped, core.begin_emission()))
+            .unwrap_or_else(|_| self.emit_none())
+    }
+
+    // Runs `replay` on the current thread as an emission of the signal, subject to its reentrancy policy and maximum
+    // depth. `replay` is passed `true` if it may execute slots and `false` if the emission was refused. A queued replay
+    // runs once the outermost emission of the signal on the current thread has finished.
+    pub fn replay<F>(self: &Arc<Self>, replay: F)
+    where
+        F: FnOnce(bool) + 'static
+    {
+        let replay = Cell::new(Some(replay));
+        let queued = || -> Box<dyn FnOnce()> {
+            let replay = replay.take().unwrap();
+            let shared = self.shared.clone();
+            Box::new(move || {
+                if let Some(shared) = shared.upgrade() {
+                    let core = shared.read().unwrap().clone();
+                    core.replay(replay);
+                }
+            })
+        };
+
+        let result = self.guarded(queued, |_| (replay.take().unwrap())(true));
+        if let (Err(_), Some(replay)) = (result, replay.take()) {
+            replay(false);
+        }
+    }
+
+    // Whether emissions need to be tracked on the current thread. Tracking is only needed to enforce a reentrancy
+    // policy or maximum depth, and to provide an emission context, so the common case skips it entirely.
+    fn tracked(&self) -> bool {
+        self.reentrancy.policy != ReentrancyPolicy::Allow
+            || self.reentrancy.max_depth.is_some()
+            || self.contextual_slots > 0
+    }
+
+    // The output of an emission that executes no slots.
+    fn emit_none(&self) -> C::Output {
+        self.combiner.combine(std::iter::empty())
+    }
+
+    // Creates the job that executes a queued emission. The queued emission uses the signal as it is when the emission
+    // runs, not as it is now.
+    fn queued_emission(&self, args: &Args) -> Box<dyn FnOnce()> {
+        let shared = self.shared.clone();
+        let args = args.clone();
+        Box::new(move || {
+            if let Some(shared) = shared.upgrade() {
+                let core = shared.read().unwrap().clone();
+                core.emit(&args);
+            }
+        })
+    }
+
+    // Applies the signal's reentrancy policy and maximum depth to an emission on the current thread. A nested emission
+    // that must be queued is replaced by the job returned from `queued`. Emissions that are queued while `emit` runs
+    // are executed once the outermost emission has finished.
+    fn guarded<T>(self: &Arc<Self>, queued: impl FnOnce() -> Box<dyn FnOnce()>, emit: impl FnOnce(&Self) -> T) -> Result<T, ReentrancyError> {
+        let depth = reentrancy::depth(self.id);
+        if depth > 0 {
+            match self.reentrancy.policy {
+                ReentrancyPolicy::Allow => {},
+                ReentrancyPolicy::Skip => return Err(ReentrancyError::Skipped),
+                ReentrancyPolicy::Queue => {
+                    reentrancy::enqueue(self.id, queued());
+                    return Err(ReentrancyError::Queued);
+                }
+            }
+        }
+
+        if let Some(max_depth) = self.reentrancy.max_depth {
+            if depth >= max_depth {
+                if let Some(hook) = &self.reentrancy.hook {
+                    hook(depth + 1);
+                }
+
+                return Err(ReentrancyError::MaxDepthExceeded { depth: depth + 1 });
+            }
+        }
+
+        let guard = DepthGuard::enter(self.id);
+        let output = emit(self);
+        std::mem::drop(guard);
+
+        if depth == 0 {
+            while let Some(job) = reentrancy::dequeue(self.id) {
+                job();
+            }
+        }
+
+        Ok(output)
+    }
+
+    // Starts a new emission on the current thread.
+    fn begin_emission(&self) -> Emission {
+        Emission::new(self.emission_count.fetch_add(1, Ordering::SeqCst), reentrancy::depth(self.id))
+    }
+
+    fn emit_slots(&self, args: &Args, emission: Emission) -> C::Output {
+        let blocked = self.blocked();
+        let iter = self.slots
+            .iter()
+            .filter(|slot| !blocked && slot.connected() && !slot.blocked())
+            .enumerate()
+            .filter_map(|(index, slot)| {
+                if emission.stopped() {
                     None
+                } else {
+                    slot.emit(args.clone(), &emission, index)
                 }
+            });
+
+        let output = self.combiner.combine(iter);
+        emission.finish();
+        output
+    }
+
+    fn emit_slots_parallel(&self, args: &Args, grouped: bool, emission: Emission) -> C::Output
+    where
+        Args: Send + Sync,
+        R: Send
+    {
+        let blocked = self.blocked();
+        let slots: Vec<&Arc<Slot<Args, R, G>>> = self.slots
+            .iter()
+            .filter(|slot| !blocked && slot.connected() && !slot.blocked())
+            .collect();
+
+        let mut results: Vec<Option<R>> = Vec::with_capacity(slots.len());
+        if grouped {
+            let mut start = 0usize;
+            while start < slots.len() && !emission.stopped() {
+                let group = &slots[start].key.0;
+                let end = slots[start..]
+                    .iter()
+                    .position(|slot| slot.key.0 != *group)
+                    .map_or(slots.len(), |offset| start + offset);
+
+                results.extend(run_parallel(self.id, &slots[start..end], start, args, &emission));
+                start = end;
             }
-        );
+        } else {
+            results.extend(run_parallel(self.id, &slots, 0, args, &emission));
+        }
 
-        self.combiner.combine(iter)
+        let output = self.combiner.combine(results.into_iter().flatten());
+        emission.finish();
+        output
     }
 
-    fn connect_impl(&mut self, slot_func: SlotFunc<Args, R>, group: Group<G>, pos: Position, connected: Arc<AtomicBool>, blocker_count: Arc<AtomicUsize>)
+    fn connect_impl(&mut self, slot_func: SlotFunc<Args, R, G>, group: Group<G>, pos: Position, connected: Arc<AtomicBool>, blocker_count: Arc<AtomicUsize>, id: u64)
     {
+        if let SlotFunc::Contextual(_) = slot_func {
+            self.contextual_slots += 1;
+        }
+
         let new_slot: Slot<Args, R, G> = Slot {
             func: slot_func,
-            connected: connected,
-            blocker_count: blocker_count,
-            key: (group, next_position(&pos))
+            connected,
+            blocker_count,
+            key: (group, next_position(&pos)),
+            id
         };
 
         self.slots.insert(Arc::new(new_slot));
     }
 
-    pub fn connect<F>(&mut self, f: F, group: Group<G>, pos: Position, make_conn: impl FnOnce(Weak<AtomicBool>, Weak<AtomicUsize>) -> Connection) -> Connection
+    pub fn connect<F>(&mut self, f: F, group: Group<G>, pos: Position, make_conn: impl FnOnce(Weak<AtomicBool>, Weak<AtomicUsize>, u64) -> Connection) -> Connection
     where
         F: Fn(Args) -> R + Send + Sync + 'static
     {
         let connected = Arc::new(AtomicBool::new(true));
         let blocker_count = Arc::new(AtomicUsize::new(0usize));
-        let conn =         make_conn(Arc::downgrade(&connected), Arc::downgrade(&blocker_count));
+        let id = next_id();
+        let conn = make_conn(Arc::downgrade(&connected), Arc::downgrade(&blocker_count), id);
 
-        self.connect_impl(SlotFunc::Basic(Box::new(f)), group, pos, connected, blocker_count);
+        self.connect_impl(SlotFunc::Basic(Box::new(f)), group, pos, connected, blocker_count, id);
         conn
     }
 
-    pub fn connect_extended<F>(&mut self, f: F, group: Group<G>, pos: Position, make_conn: impl FnOnce(Weak<AtomicBool>, Weak<AtomicUsize>) -> Connection) -> Connection
+    pub fn connect_extended<F>(&mut self, f: F, group: Group<G>, pos: Position, make_conn: impl FnOnce(Weak<AtomicBool>, Weak<AtomicUsize>, u64) -> Connection) -> Connection
     where
         F: Fn(Connection, Args) -> R + Send + Sync + 'static
     {
         let connected = Arc::new(AtomicBool::new(true));
         let blocker_count = Arc::new(AtomicUsize::new(0usize));
-        let conn =         make_conn(Arc::downgrade(&connected), Arc::downgrade(&blocker_count));
+        let id = next_id();
+        let conn = make_conn(Arc::downgrade(&connected), Arc::downgrade(&blocker_count), id);
+
+        self.connect_impl(SlotFunc::Extended((Box::new(f), conn.clone())), group, pos, connected, blocker_count, id);
+        conn
+    }
+
+    pub fn connect_with_context<F>(&mut self, f: F, group: Group<G>, pos: Position, make_conn: impl FnOnce(Weak<AtomicBool>, Weak<AtomicUsize>, u64) -> Connection) -> Connection
+    where
+        F: Fn(&EmissionContext<G>, Args) -> R + Send + Sync + 'static
+    {
+        self.connect_partial_with_context(move |ctx, args| Some(f(ctx, args)), group, pos, make_conn)
+    }
+
+    pub fn connect_partial_with_context<F>(&mut self, f: F, group: Group<G>, pos: Position, make_conn: impl FnOnce(Weak<AtomicBool>, Weak<AtomicUsize>, u64) -> Connection) -> Connection
+    where
+        F: Fn(&EmissionContext<G>, Args) -> Option<R> + Send + Sync + 'static
+    {
+        let connected = Arc::new(AtomicBool::new(true));
+        let blocker_count = Arc::new(AtomicUsize::new(0usize));
+        let id = next_id();
+        let conn = make_conn(Arc::downgrade(&connected), Arc::downgrade(&blocker_count), id);
 
-        self.connect_impl(SlotFunc::Extended((Box::new(f), conn.clone())), group, pos, connected, blocker_count);
+        self.connect_impl(SlotFunc::Contextual((Box::new(f), conn.clone())), group, pos, connected, blocker_count, id);
+        conn
+    }
+
+    pub fn connect_partial<F>(&mut self, f: F, group: Group<G>, pos: Position, make_conn: impl FnOnce(Weak<AtomicBool>, Weak<AtomicUsize>, u64) -> Connection) -> Connection
+    where
+        F: Fn(&Connection, Args) -> Option<R> + Send + Sync + 'static
+    {
+        let connected = Arc::new(AtomicBool::new(true));
+        let blocker_count = Arc::new(AtomicUsize::new(0usize));
+        let id = next_id();
+        let conn = make_conn(Arc::downgrade(&connected), Arc::downgrade(&blocker_count), id);
+
+        self.connect_impl(SlotFunc::Partial((Box::new(f), conn.clone())), group, pos, connected, blocker_count, id);
         conn
     }
 
@@ -209,13 +458,185 @@ where
 
     pub fn clear(&mut self) {
         self.slots.clear();
+        self.contextual_slots = 0;
     }
 
     pub fn cleanup(&mut self) {
         self.slots.retain(|slot| slot.connected());
+        self.count_contextual_slots();
+    }
+
+    fn count_contextual_slots(&mut self) {
+        self.contextual_slots = self.slots
+            .iter()
+            .filter(|slot| matches!(slot.func, SlotFunc::Contextual(_)))
+            .count();
+    }
+
+    // Removes the slots with the given IDs and returns their connected flags. The slots are not marked as
+    // disconnected, which is up to the caller.
+    pub fn remove(&mut self, ids: &HashSet<u64>) -> Vec<Arc<AtomicBool>> {
+        let mut removed = Vec::new();
+        self.slots.retain(|slot| {
+            if ids.contains(&slot.id) {
+                removed.push(slot.connected.clone());
+                false
+            } else {
+                true
+            }
+        });
+
+        self.count_contextual_slots();
+        removed
+    }
+
+    pub fn slot_ids(&self) -> impl Iterator<Item = u64> + '_ {
+        self.slots.iter().map(|slot| slot.id)
+    }
+
+    pub fn id(&self) -> u64 {
+        self.id
+    }
+
+    pub(crate) fn reentrancy(&self) -> &ReentrancyConfig {
+        &self.reentrancy
+    }
+
+    pub(crate) fn reentrancy_mut(&mut self) -> &mut ReentrancyConfig {
+        &mut self.reentrancy
     }
 
     pub fn count(&self) -> usize {
         self.slots.iter().filter(|slot| slot.connected()).count()
     }
+
+    pub fn blocked(&self) -> bool {
+        self.blocker_count.load(Ordering::SeqCst) != 0usize
+    }
+
+    pub fn blocker_count(&self) -> usize {
+        self.blocker_count.load(Ordering::SeqCst)
+    }
+
+    pub fn weak_blocker_count(&self) -> Weak<AtomicUsize> {
+        Arc::downgrade(&self.blocker_count)
+    }
+
+    // Executes only the slot with the given ID, as a separate emission, unless the slot or the signal is blocked. Used
+    // within `replay` to replay past emissions to a newly connected slot.
+    pub fn emit_slot(&self, id: u64, args: Args) -> Option<R> {
+        let (index, slot) = self.slots.iter().enumerate().find(|(_, slot)| slot.id == id)?;
+        if self.blocked() || !slot.connected() || slot.blocked() {
+            return None;
+        }
+
+        let emission = self.begin_emission();
+        let output = slot.emit(args, &emission, index);
+        emission.finish();
+        output
+    }
+
+    pub fn slot_info(&self) -> Vec<SlotInfo> {
+        self.slots
+            .iter()
+            .filter(|slot| slot.connected())
+            .map(|slot| SlotInfo {
+                id: slot.id,
+                group: match slot.key.0 {
+                    Group::Front => SlotGroup::Front,
+                    Group::Named(_) => SlotGroup::Named,
+                    Group::Back => SlotGroup::Back
+                },
+                blocked: slot.blocked()
+            })
+            .collect()
+    }
+}
+
+// The emission used when the signal does not need tracking. None of the signal's slots can observe it, so it is never
+// stopped and its sequence number and depth are meaningless.
+fn untracked_emission() -> Emission {
+    Emission::new(0, 0)
+}
+
+// Executes the given slots concurrently and returns their results in the same order as the slots. The slots are split
+// into contiguous chunks, one per worker thread, and at most as many workers are used as the machine has available
+// parallelism (but at least two). A worker skips its remaining slots once propagation has been stopped. Workers
+// continue the emission at its depth on the current thread. Every worker is joined before a panic raised by a slot is
+// resumed.
+#[cfg(not(feature = "rayon"))]
+fn run_parallel<Args, R, G>(signal_id: u64, slots: &[&Arc<Slot<Args, R, G>>], first_index: usize, args: &Args, emission: &Emission) -> Vec<Option<R>>
+where
+    Args: Clone + Send + Sync + 'static,
+    R: Send + 'static,
+    G: Ord + Send + Sync + 'static
+{
+    match slots {
+        [] => return Vec::new(),
+        [slot] => return vec!(slot.emit(args.clone(), emission, first_index)),
+        _ => {}
+    }
+
+    let workers = thread::available_parallelism()
+        .map_or(2, |n| n.get())
+        .clamp(2, slots.len());
+    let chunk_size = (slots.len() + workers - 1) / workers;
+    let chunks: Vec<thread::Result<Vec<Option<R>>>> = thread::scope(|scope| {
+        let handles: Vec<thread::ScopedJoinHandle<Vec<Option<R>>>> = slots
+            .chunks(chunk_size)
+            .enumerate()
+            .map(|(chunk, slots)| {
+                let first_index = first_index + chunk * chunk_size;
+                scope.spawn(move || reentrancy::inherit(signal_id, emission.depth(), || {
+                    slots
+                        .iter()
+                        .enumerate()
+                        .map(|(offset, slot)| {
+                            if emission.stopped() {
+                                None
+                            } else {
+                                slot.emit(args.clone(), emission, first_index + offset)
+                            }
+                        })
+                        .collect()
+                }))
+            })
+            .collect();
+
+        handles.into_iter().map(|handle| handle.join()).collect()
+    });
+
+    let mut results = Vec::with_capacity(slots.len());
+    for chunk in chunks {
+        match chunk {
+            Ok(chunk) => results.extend(chunk),
+            Err(err) => panic::resume_unwind(err)
+        }
+    }
+
+    results
+}
+
+// Executes the given slots on rayon's global thread pool and returns their results in the same order as the slots.
+// Each slot continues the emission at its depth on the current thread.
+#[cfg(feature = "rayon")]
+fn run_parallel<Args, R, G>(signal_id: u64, slots: &[&Arc<Slot<Args, R, G>>], first_index: usize, args: &Args, emission: &Emission) -> Vec<Option<R>>
+where
+    Args: Clone + Send + Sync + 'static,
+    R: Send + 'static,
+    G: Ord + Send + Sync + 'static
+{
+    use rayon::prelude::*;
+
+    slots
+        .par_iter()
+        .enumerate()
+        .map(|(offset, slot)| {
+            if emission.stopped() {
+                None
+            } else {
+                reentrancy::inherit(signal_id, emission.depth(), || slot.emit(args.clone(), emission, first_index + offset))
+            }
+        })
+        .collect()
 }
\ No newline at end of file
@@ -0,0 +1,201 @@
+// Copyright Christian Daley 2021
+// Copyright Frank Mori Hess 2007-2008.
+// Distributed under the Boost Software License, Version 1.0. 
+// See http://www.boost.org/LICENSE_1_0.txt
+
+use std::collections::VecDeque;
+use std::error::Error;
+use std::fmt;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll, Waker};
+
+use futures_core::Stream;
+use futures_sink::Sink;
+
+use crate::{Signal, EmitHandle, ScopedConnection, Group, Position};
+use crate::combiner::Combiner;
+
+/// Determines what a [SignalStream] does when the signal is emitted while the stream's buffer is full.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum OverflowPolicy {
+    /// The oldest buffered arguments are discarded to make room for the new ones.
+    DropOldest,
+    /// The new arguments are discarded and the buffer is left unchanged.
+    DropNewest
+}
+
+struct StreamState<Args> {
+    buffer: VecDeque<Args>,
+    capacity: usize,
+    policy: OverflowPolicy,
+    waker: Option<Waker>,
+    closed: bool
+}
+
+impl<Args> StreamState<Args> {
+    fn push(&mut self, args: Args) {
+        if self.buffer.len() >= self.capacity {
+            match self.policy {
+                OverflowPolicy::DropOldest => { self.buffer.pop_front(); },
+                OverflowPolicy::DropNewest => return
+            }
+        }
+
+        self.buffer.push_back(args);
+        if let Some(waker) = self.waker.take() {
+            waker.wake();
+        }
+    }
+
+    fn close(&mut self) {
+        self.closed = true;
+        if let Some(waker) = self.waker.take() {
+            waker.wake();
+        }
+    }
+}
+
+// Owned by the slot that feeds a stream. Closes the stream when the slot is dropped,
+// which happens when the signal itself is dropped or cleared.
+struct StreamFeeder<Args> {
+    state: Arc<Mutex<StreamState<Args>>>
+}
+
+impl<Args> Drop for StreamFeeder<Args> {
+    fn drop(&mut self) {
+        self.state.lock().unwrap().close();
+    }
+}
+
+/// A [Stream] of the arguments a signal is emitted with. Created by [Signal::stream].
+///
+/// Emissions are buffered until the stream is polled. The buffer holds a fixed number of emissions,
+/// and the stream's [OverflowPolicy] determines which emissions are discarded once it is full.
+/// The stream ends once the signal is dropped or cleared and the buffer has been drained. Dropping the stream
+/// disconnects it from the signal.
+pub struct SignalStream<Args> {
+    state: Arc<Mutex<StreamState<Args>>>,
+    _conn: ScopedConnection
+}
+
+impl<Args> Stream for SignalStream<Args> {
+    type Item = Args;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Args>> {
+        let mut state = self.state.lock().unwrap();
+        if let Some(args) = state.buffer.pop_front() {
+            Poll::Ready(Some(args))
+        } else if state.closed {
+            Poll::Ready(None)
+        } else {
+            state.waker = Some(cx.waker().clone());
+            Poll::Pending
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let state = self.state.lock().unwrap();
+        (state.buffer.len(), if state.closed { Some(state.buffer.len()) } else { None })
+    }
+}
+
+impl<Args, R, C, G> Signal<Args, R, C, G>
+where
+    Args: Clone + 'static,
+    R: 'static,
+    C: Combiner<R> + 'static,
+    G: Ord + Send + Sync + 'static
+{
+    /// Creates a [SignalStream] that yields the arguments of every subsequent emission of the signal.
+    /// At most `capacity` emissions are buffered; `policy` decides which emissions are discarded when
+    /// the buffer is full. A `capacity` of zero is treated as one. The stream is fed by a slot connected at the back of
+    /// [Group::Back] that does not contribute to the signal's return value.
+    ///
+    /// Requires the `futures` feature.
+    /// # Example
+    /// ```
+    /// use signals2::*;
+    /// use signals2::stream::OverflowPolicy;
+    /// use futures::{executor, StreamExt};
+    ///
+    /// let sig: Signal<(i32,)> = Signal::new();
+    /// let stream = sig.stream(2, OverflowPolicy::DropOldest);
+    /// sig.emit(1);
+    /// sig.emit(2);
+    /// sig.emit(3);
+    /// std::mem::drop(sig);
+    ///
+    /// let values: Vec<(i32,)> = executor::block_on(stream.collect());
+    /// assert_eq!(values, vec!((2,), (3,)));
+    /// ```
+    pub fn stream(&self, capacity: usize, policy: OverflowPolicy) -> SignalStream<Args>
+    where
+        Args: Send
+    {
+        let state = Arc::new(Mutex::new(StreamState {
+            buffer: VecDeque::new(),
+            capacity: capacity.max(1),
+            policy,
+            waker: None,
+            closed: false
+        }));
+
+        let feeder = StreamFeeder {
+            state: state.clone()
+        };
+
+        let conn = self.connect_partial(move |_, args| {
+            feeder.state.lock().unwrap().push(args);
+            None
+        }, Group::Back, Position::Back);
+
+        SignalStream {
+            state,
+            _conn: conn.scoped()
+        }
+    }
+}
+
+/// The error produced by the [Sink] implementation of [EmitHandle] when the underlying signal no longer exists.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SinkError;
+
+impl fmt::Display for SinkError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "the signal no longer exists")
+    }
+}
+
+impl Error for SinkError {}
+
+/// Every item sent into an `EmitHandle` emits the underlying signal with that item as its arguments.
+/// The signal's return value is discarded. Requires the `futures` feature.
+impl<Args, R, C, G> Sink<Args> for EmitHandle<Args, R, C, G>
+where
+    Args: Clone + 'static,
+    R: 'static,
+    C: Combiner<R> + 'static,
+    G: Ord + Send + Sync + 'static
+{
+    type Error = SinkError;
+
+    fn poll_ready(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<Result<(), SinkError>> {
+        Poll::Ready(self.weak_sig.upgrade().map(|_| ()).ok_or(SinkError))
+    }
+
+    fn start_send(self: Pin<&mut Self>, item: Args) -> Result<(), SinkError> {
+        self.weak_sig
+            .upgrade()
+            .map(|sig| { sig.emit_args(&item); })
+            .ok_or(SinkError)
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<Result<(), SinkError>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn poll_close(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<Result<(), SinkError>> {
+        Poll::Ready(Ok(()))
+    }
+}
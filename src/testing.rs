@@ -0,0 +1,409 @@
+// Copyright Christian Daley 2021
+// Copyright Frank Mori Hess 2007-2008.
+// Distributed under the Boost Software License, Version 1.0. 
+// See http://www.boost.org/LICENSE_1_0.txt
+
+use std::fmt::Debug;
+use std::sync::{Arc, Mutex, Condvar};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::{Signal, Group, Position, ScopedConnection};
+use crate::combiner::Combiner;
+
+/// A single emission recorded by a [SignalSpy].
+#[derive(Clone, Debug)]
+pub struct Emission<Args> {
+    /// The arguments the signal was emitted with.
+    pub args: Args,
+    /// The position of the emission among all of the emissions recorded by the spy, starting at 0. Sequence numbers
+    /// are not reused after emissions are removed with [SignalSpy::take].
+    pub seq: u64,
+    /// The time at which the emission was recorded.
+    pub timestamp: Instant
+}
+
+struct SpyState<Args> {
+    emissions: Vec<Emission<Args>>,
+    next_seq: u64
+}
+
+/// Records every emission of a signal, for use in tests. A `SignalSpy` is connected to the signal through a
+/// [ScopedConnection] at the back of [Group::Back], so it observes every emission after all of the signal's other
+/// slots have run. The spy does not contribute to the signal's return value, and it is disconnected when it is dropped.
+/// # Example
+/// ```
+/// use signals2::*;
+/// use signals2::testing::SignalSpy;
+///
+/// let sig: Signal<(i32, &'static str)> = Signal::new();
+/// let spy = SignalSpy::new(&sig);
+/// spy.assert_not_emitted();
+///
+/// sig.emit(1, "one");
+/// sig.emit(2, "two");
+/// assert_eq!(spy.count(), 2);
+/// assert_eq!(spy.last(), Some((2, "two")));
+/// spy.assert_emitted_with(&(1, "one"));
+///
+/// let emissions = spy.take();
+/// assert_eq!(emissions[0].args, (1, "one"));
+/// assert_eq!(spy.count(), 0);
+/// ```
+pub struct SignalSpy<Args> {
+    state: Arc<(Mutex<SpyState<Args>>, Condvar)>,
+    _conn: ScopedConnection
+}
+
+impl<Args> SignalSpy<Args>
+where
+    Args: Clone + Send + 'static
+{
+    /// Creates a new spy that records the emissions of `sig`.
+    pub fn new<R, C, G>(sig: &Signal<Args, R, C, G>) -> Self
+    where
+        R: 'static,
+        C: Combiner<R> + 'static,
+        G: Ord + Send + Sync + 'static
+    {
+        let state = Arc::new((Mutex::new(SpyState { emissions: Vec::new(), next_seq: 0 }), Condvar::new()));
+        let slot_state = state.clone();
+
+        let conn = sig.connect_partial(move |_, args| {
+            let (lock, cvar) = &*slot_state;
+            let mut state = lock.lock().unwrap();
+            let seq = state.next_seq;
+            state.next_seq += 1;
+            state.emissions.push(Emission { args, seq, timestamp: Instant::now() });
+            cvar.notify_all();
+            None
+        }, Group::Back, Position::Back).scoped();
+
+        SignalSpy {
+            state,
+            _conn: conn
+        }
+    }
+
+    /// Returns the number of emissions that have been recorded and not yet removed with [take](SignalSpy::take).
+    pub fn count(&self) -> usize {
+        self.state.0.lock().unwrap().emissions.len()
+    }
+
+    /// Removes and returns all of the recorded emissions, oldest first.
+    pub fn take(&self) -> Vec<Emission<Args>> {
+        std::mem::take(&mut self.state.0.lock().unwrap().emissions)
+    }
+
+    /// Returns a copy of all of the recorded emissions, oldest first.
+    pub fn emissions(&self) -> Vec<Emission<Args>> {
+        self.state.0.lock().unwrap().emissions.clone()
+    }
+
+    /// Returns the arguments of the most recent recorded emission.
+    pub fn last(&self) -> Option<Args> {
+        self.state.0.lock().unwrap().emissions.last().map(|emission| emission.args.clone())
+    }
+
+    /// Blocks the current thread until at least `n` emissions have been recorded. Returns `false` if `timeout`
+    /// elapses first.
+    pub fn wait_for(&self, n: usize, timeout: Duration) -> bool {
+        let (lock, cvar) = &*self.state;
+        let state = lock.lock().unwrap();
+        let (state, _) = cvar.wait_timeout_while(state, timeout, |state| state.emissions.len() < n).unwrap();
+        state.emissions.len() >= n
+    }
+
+    /// Panics if none of the recorded emissions have arguments equal to `args`.
+    pub fn assert_emitted_with(&self, args: &Args)
+    where
+        Args: PartialEq + Debug
+    {
+        let recorded = self.recorded_args();
+        if !recorded.contains(args) {
+            panic!("signal was not emitted with {:?}, recorded emissions: {:?}", args, recorded);
+        }
+    }
+
+    /// Panics if any emissions have been recorded.
+    pub fn assert_not_emitted(&self)
+    where
+        Args: Debug
+    {
+        let recorded = self.recorded_args();
+        if !recorded.is_empty() {
+            panic!("signal was emitted {} time(s): {:?}", recorded.len(), recorded);
+        }
+    }
+
+    // Copies the arguments out so that the assertions do not panic while holding the lock.
+    fn recorded_args(&self) -> Vec<Args> {
+        self.state.0.lock().unwrap().emissions.iter().map(|emission| emission.args.clone()).collect()
+    }
+}
+
+type ReturnFunc<Args, R> = Arc<dyn Fn(Args) -> R + Send + Sync>;
+
+struct MockState<Args, R> {
+    name: String,
+    returning: ReturnFunc<Args, R>,
+    times: Option<usize>,
+    sequence: Option<(Sequence, usize)>,
+    calls: Vec<Args>,
+    errors: Vec<String>
+}
+
+/// A slot for use in tests that records its calls and verifies expectations about them. The function returned by
+/// [slot](MockSlot::slot) is connected to a signal like any other closure, and the one returned by
+/// [tuple_slot](MockSlot::tuple_slot) accepts the arguments as a tuple. A `MockSlot` can be told what to return
+/// with [returning](MockSlot::returning), how many times it expects to be called with [times](MockSlot::times),
+/// and where its calls belong relative to other mocks with [in_sequence](MockSlot::in_sequence).
+///
+/// Expectations are verified when the `MockSlot` is dropped (unless the thread is already panicking), or explicitly
+/// with [verify](MockSlot::verify). A failed expectation causes a panic that describes every violation.
+/// # Example
+/// ```
+/// use signals2::*;
+/// use signals2::testing::{MockSlot, Sequence};
+///
+/// let sig: Signal<(i32,), i32> = Signal::new();
+/// let seq = Sequence::new();
+///
+/// let first: MockSlot<(i32,), i32> = MockSlot::new().named("first").times(1).in_sequence(&seq);
+/// let second = MockSlot::new().named("second").returning(|(x,)| x * 2).times(1).in_sequence(&seq);
+/// sig.connect_position(second.slot(), Position::Back);
+/// sig.connect_position(first.slot(), Position::Front);
+///
+/// assert_eq!(sig.emit(5), Some(10));
+/// assert_eq!(first.calls(), vec!((5,)));
+/// ```
+pub struct MockSlot<Args, R = ()>
+where
+    Args: Clone + Send + 'static,
+    R: 'static
+{
+    state: Arc<Mutex<MockState<Args, R>>>
+}
+
+impl<Args, R> MockSlot<Args, R>
+where
+    Args: Clone + Send + 'static,
+    R: 'static
+{
+    /// Creates a new mock that returns `R::default()` and has no expectations.
+    pub fn new() -> Self
+    where
+        R: Default
+    {
+        Self::from_fn(|_| R::default())
+    }
+
+    /// Creates a new mock that returns the result of `f` and has no expectations. Useful when `R` does not implement
+    /// [Default].
+    pub fn from_fn<F>(f: F) -> Self
+    where
+        F: Fn(Args) -> R + Send + Sync + 'static
+    {
+        let state = MockState {
+            name: String::from("mock slot"),
+            returning: Arc::new(f),
+            times: None,
+            sequence: None,
+            calls: Vec::new(),
+            errors: Vec::new()
+        };
+
+        MockSlot {
+            state: Arc::new(Mutex::new(state))
+        }
+    }
+
+    /// Sets the name that identifies the mock in failure messages.
+    pub fn named(self, name: &str) -> Self {
+        self.state.lock().unwrap().name = String::from(name);
+        self
+    }
+
+    /// Sets the function that computes the mock's return value from the arguments it is called with.
+    pub fn returning<F>(self, f: F) -> Self
+    where
+        F: Fn(Args) -> R + Send + Sync + 'static
+    {
+        self.state.lock().unwrap().returning = Arc::new(f);
+        self
+    }
+
+    /// Expects the mock to be called exactly `n` times.
+    pub fn times(self, n: usize) -> Self {
+        self.state.lock().unwrap().times = Some(n);
+        self
+    }
+
+    /// Adds the mock to `seq`. Mocks in a sequence are expected to be called in the order in which they were added to
+    /// it: calling a mock after a mock that was added to the sequence later is a violation.
+    pub fn in_sequence(self, seq: &Sequence) -> Self {
+        let position = seq.add();
+        self.state.lock().unwrap().sequence = Some((seq.clone(), position));
+        self
+    }
+
+    /// Returns a function that records its calls to the mock and accepts its arguments as a single tuple. Connect it
+    /// to a signal with [connect_tuple](crate::Connect::connect_tuple) or one of its variants. To connect the mock
+    /// with [connect](crate::Connect1::connect) and its variants use [slot](MockSlot::slot) instead.
+    pub fn tuple_slot(&self) -> impl Fn(Args) -> R + Send + Sync + 'static {
+        let state = self.state.clone();
+        move |args| {
+            let returning = {
+                let mut state = state.lock().unwrap();
+                let MockState { name, times, sequence, calls, errors, .. } = &mut *state;
+
+                calls.push(args.clone());
+                if let Some(times) = times {
+                    if calls.len() == *times + 1 {
+                        errors.push(format!("{} was called more than the expected {} time(s)", name, times));
+                    }
+                }
+
+                if let Some((seq, position)) = sequence {
+                    if let Err(err) = seq.record(*position, name) {
+                        errors.push(err);
+                    }
+                }
+
+                state.returning.clone()
+            };
+
+            returning(args)
+        }
+    }
+
+    /// Returns the arguments of every call to the mock so far, oldest first.
+    pub fn calls(&self) -> Vec<Args> {
+        self.state.lock().unwrap().calls.clone()
+    }
+
+    /// Returns the number of times the mock has been called.
+    pub fn call_count(&self) -> usize {
+        self.state.lock().unwrap().calls.len()
+    }
+
+    /// Panics if any of the mock's expectations have been violated or, when a call count was set with
+    /// [times](MockSlot::times), if the mock has not been called that many times.
+    pub fn verify(&self) {
+        let errors = {
+            let state = self.state.lock().unwrap();
+            let mut errors = state.errors.clone();
+            if let Some(times) = state.times {
+                if state.calls.len() < times {
+                    errors.push(format!("{} was called {} time(s), expected {}", state.name, state.calls.len(), times));
+                }
+            }
+
+            errors
+        };
+
+        if !errors.is_empty() {
+            panic!("mock expectations failed:\n{}", errors.join("\n"));
+        }
+    }
+}
+
+macro_rules! impl_mock_slot {
+    ($($args:ident)*; $($params:ident)*) => {
+        impl<$($args,)* R> MockSlot<($($args,)*), R>
+        where
+            $($args: Clone + Send + 'static,)*
+            R: 'static
+        {
+            /// Returns a function that records its calls to the mock and accepts its arguments individually. Connect
+            /// it to a signal with [connect](crate::Connect1::connect) or one of its variants. The mock's argument
+            /// types must already be known when this is called, for example from a type annotation.
+            pub fn slot(&self) -> impl Fn($($args),*) -> R + Send + Sync + 'static {
+                let slot = self.tuple_slot();
+                move |$($params),*| slot(($($params,)*))
+            }
+        }
+    };
+}
+
+impl_mock_slot!(;);
+impl_mock_slot!(T0; a);
+impl_mock_slot!(T0 T1; a b);
+impl_mock_slot!(T0 T1 T2; a b c);
+impl_mock_slot!(T0 T1 T2 T3; a b c d);
+impl_mock_slot!(T0 T1 T2 T3 T4; a b c d e);
+impl_mock_slot!(T0 T1 T2 T3 T4 T5; a b c d e f);
+impl_mock_slot!(T0 T1 T2 T3 T4 T5 T6; a b c d e f g);
+impl_mock_slot!(T0 T1 T2 T3 T4 T5 T6 T7; a b c d e f g h);
+impl_mock_slot!(T0 T1 T2 T3 T4 T5 T6 T7 T8; a b c d e f g h i);
+impl_mock_slot!(T0 T1 T2 T3 T4 T5 T6 T7 T8 T9; a b c d e f g h i j);
+impl_mock_slot!(T0 T1 T2 T3 T4 T5 T6 T7 T8 T9 T10; a b c d e f g h i j k);
+impl_mock_slot!(T0 T1 T2 T3 T4 T5 T6 T7 T8 T9 T10 T11; a b c d e f g h i j k l);
+
+impl<Args, R> Default for MockSlot<Args, R>
+where
+    Args: Clone + Send + 'static,
+    R: Default + 'static
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<Args, R> Drop for MockSlot<Args, R>
+where
+    Args: Clone + Send + 'static,
+    R: 'static
+{
+    fn drop(&mut self) {
+        if !thread::panicking() {
+            self.verify();
+        }
+    }
+}
+
+struct SequenceState {
+    len: usize,
+    last: Option<(usize, String)>
+}
+
+/// An ordering constraint shared by several [MockSlots](MockSlot). Cloning a `Sequence` produces a handle to the
+/// same sequence.
+#[derive(Clone)]
+pub struct Sequence {
+    state: Arc<Mutex<SequenceState>>
+}
+
+impl Sequence {
+    /// Creates a new, empty sequence.
+    pub fn new() -> Self {
+        Sequence {
+            state: Arc::new(Mutex::new(SequenceState { len: 0, last: None }))
+        }
+    }
+
+    fn add(&self) -> usize {
+        let mut state = self.state.lock().unwrap();
+        state.len += 1;
+        state.len - 1
+    }
+
+    fn record(&self, position: usize, name: &str) -> Result<(), String> {
+        let mut state = self.state.lock().unwrap();
+        match &state.last {
+            Some((last, last_name)) if *last > position => {
+                Err(format!("{} was called after {}, but comes before it in the sequence", name, last_name))
+            },
+            _ => {
+                state.last = Some((position, String::from(name)));
+                Ok(())
+            }
+        }
+    }
+}
+
+impl Default for Sequence {
+    fn default() -> Self {
+        Self::new()
+    }
+}
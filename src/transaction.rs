@@ -0,0 +1,179 @@
+// Copyright Christian Daley 2021
+// Copyright Frank Mori Hess 2007-2008.
+// Distributed under the Boost Software License, Version 1.0. 
+// See http://www.boost.org/LICENSE_1_0.txt
+
+use std::cell::RefCell;
+use std::collections::HashSet;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::{Arc, atomic::Ordering};
+
+use crate::{Signal, Group, Position};
+use crate::signal_core::{SignalCore, WeakCore};
+use crate::combiner::{Combiner, DefaultCombiner};
+use crate::connect::{Connect, Connection, ConnectionImpl};
+use crate::context::EmissionContext;
+
+/// A set of changes to a signal's slots and combiner that is applied all at once. Created by
+/// [Signal::transaction]. A `SignalTransaction` implements the same [connect](crate::connect) traits as a
+/// [Signal], so slots are connected to a transaction the same way they are connected to a signal.
+///
+/// None of the changes made through a transaction are visible to emitters until the transaction is committed.
+/// The connections returned by a transaction report that their slots are connected immediately, even though
+/// the slots will not be executed until the transaction is committed.
+pub struct SignalTransaction<Args, R = (), C = DefaultCombiner, G = i32>
+where
+    Args: Clone + 'static,
+    R: 'static,
+    C: Combiner<R> + 'static,
+    G: Ord + Send + Sync + 'static
+{
+    weak_core: WeakCore<Args, R, C, G>,
+    signal_id: u64,
+    core: RefCell<SignalCore<Args, R, C, G>>,
+    removed: RefCell<HashSet<u64>>
+}
+
+impl<Args, R, C, G> SignalTransaction<Args, R, C, G>
+where
+    Args: Clone + 'static,
+    R: 'static,
+    C: Combiner<R> + 'static,
+    G: Ord + Send + Sync + 'static
+{
+    /// Disconnects the slot managed by `conn` when the transaction is committed. Returns false if the slot does not
+    /// belong to the signal or has already been disconnected, true otherwise.
+    pub fn disconnect<const SCOPED: bool>(&self, conn: &ConnectionImpl<SCOPED>) -> bool {
+        if conn.signal_id() != self.signal_id || !conn.connected() {
+            return false;
+        }
+
+        let exists = self.core.borrow().slot_ids().any(|id| id == conn.id());
+        exists && self.removed.borrow_mut().insert(conn.id())
+    }
+
+    /// Disconnects all of the signal's slots when the transaction is committed, including the slots that were
+    /// connected earlier in the transaction.
+    pub fn clear(&self) {
+        self.removed.borrow_mut().extend(self.core.borrow().slot_ids());
+    }
+
+    /// Sets a new [Combiner] for the signal when the transaction is committed.
+    pub fn set_combiner(&self, combiner: C) {
+        self.core.borrow_mut().set_combiner(combiner);
+    }
+
+    /// Returns the number of slots the signal will have once the transaction is committed.
+    pub fn count(&self) -> usize {
+        let removed = self.removed.borrow();
+        self.core.borrow().slot_ids().filter(|id| !removed.contains(id)).count()
+    }
+}
+
+impl<Args, R, C, G> Connect<Args> for SignalTransaction<Args, R, C, G>
+where
+    Args: Clone + 'static,
+    R: 'static,
+    C: Combiner<R> + 'static,
+    G: Ord + Send + Sync + 'static
+{
+    type Return = R;
+    type Group = G;
+
+    fn connect_tuple_group_position<F>(&self, f: F, group: Group<G>, pos: Position) -> Connection
+    where
+        F: Fn(Args) -> R + Send + Sync + 'static
+    {
+        let make_conn = Signal::conn_factory(self.weak_core.clone(), self.signal_id);
+        self.core.borrow_mut().connect(f, group, pos, make_conn)
+    }
+
+    fn connect_tuple_group_position_extended<F>(&self, f: F, group: Group<G>, pos: Position) -> Connection
+    where
+        F: Fn(Connection, Args) -> R + Send + Sync + 'static
+    {
+        let make_conn = Signal::conn_factory(self.weak_core.clone(), self.signal_id);
+        self.core.borrow_mut().connect_extended(f, group, pos, make_conn)
+    }
+
+    fn connect_tuple_group_position_with_context<F>(&self, f: F, group: Group<G>, pos: Position) -> Connection
+    where
+        F: Fn(&EmissionContext<G>, Args) -> R + Send + Sync + 'static
+    {
+        let make_conn = Signal::conn_factory(self.weak_core.clone(), self.signal_id);
+        self.core.borrow_mut().connect_with_context(f, group, pos, make_conn)
+    }
+
+    fn connect_tuple_group_position_partial<F>(&self, f: F, group: Group<G>, pos: Position) -> Connection
+    where
+        F: Fn(&Connection, Args) -> Option<R> + Send + Sync + 'static
+    {
+        let make_conn = Signal::conn_factory(self.weak_core.clone(), self.signal_id);
+        self.core.borrow_mut().connect_partial(f, group, pos, make_conn)
+    }
+}
+
+impl<Args, R, C, G> Signal<Args, R, C, G>
+where
+    Args: Clone + 'static,
+    R: 'static,
+    C: Combiner<R> + 'static,
+    G: Ord + Send + Sync + 'static
+{
+    /// Applies several changes to the signal as a single atomic update. `f` connects slots, disconnects slots and
+    /// sets the combiner through the given [SignalTransaction], and the changes are committed once `f` returns.
+    /// Concurrent emissions execute either the slots the signal had before the transaction or the slots it has after
+    /// it, never a mix of the two. The signal's slots are only copied once, regardless of the number of changes.
+    ///
+    /// The signal is locked while `f` runs, so `f` must not use the signal (or a [Connection] to one of its slots)
+    /// directly. If `f` panics, none of the changes are applied and the panic is propagated.
+    /// # Example
+    /// ```
+    /// use signals2::*;
+    ///
+    /// let sig: Signal<(i32,), i32> = Signal::new();
+    /// let old = sig.connect(|x| x);
+    ///
+    /// let new = sig.transaction(|tx| {
+    ///     tx.disconnect(&old);
+    ///     tx.connect(|x| x * 2)
+    /// });
+    ///
+    /// assert!(!old.connected());
+    /// assert!(new.connected());
+    /// assert_eq!(sig.emit(4), Some(8));
+    /// ```
+    pub fn transaction<T, F>(&self, f: F) -> T
+    where
+        F: FnOnce(&SignalTransaction<Args, R, C, G>) -> T
+    {
+        let mut lock = self.core.write().unwrap();
+        let tx = SignalTransaction {
+            weak_core: Arc::downgrade(&self.core),
+            signal_id: lock.id(),
+            core: RefCell::new((**lock).clone()),
+            removed: RefCell::new(HashSet::new())
+        };
+
+        match panic::catch_unwind(AssertUnwindSafe(|| f(&tx))) {
+            Ok(res) => {
+                let mut core = tx.core.into_inner();
+                let removed = core.remove(&tx.removed.into_inner());
+                *lock = Arc::new(core);
+                std::mem::drop(lock);
+
+                for connected in removed {
+                    connected.store(false, Ordering::SeqCst);
+                }
+
+                res
+            },
+            Err(err) => {
+                // the slots connected by the transaction may hold connections to this signal
+                std::mem::drop(lock);
+                std::mem::drop(tx);
+                panic::resume_unwind(err)
+            }
+        }
+    }
+}
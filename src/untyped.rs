@@ -0,0 +1,151 @@
+// Copyright Christian Daley 2021
+// Copyright Frank Mori Hess 2007-2008.
+// Distributed under the Boost Software License, Version 1.0. 
+// See http://www.boost.org/LICENSE_1_0.txt
+
+use std::any::Any;
+use std::error::Error;
+use std::fmt;
+
+use crate::{Signal, SharedConnectionBlock};
+use crate::combiner::Combiner;
+
+/// The kind of group that a slot is connected to. The value of a named group is not available
+/// through an [UntypedSignal].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SlotGroup {
+    /// The unnamed "front" group.
+    Front,
+    /// A named group.
+    Named,
+    /// The unnamed "back" group.
+    Back
+}
+
+/// Metadata describing a single connected slot of a signal.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SlotInfo {
+    /// The ID of the slot, which is the same as the [id](crate::connect::ConnectionImpl::id) of its connection.
+    pub id: u64,
+    /// The group that the slot is connected to.
+    pub group: SlotGroup,
+    /// Whether the slot is currently blocked by one or more
+    /// [SharedConnectionBlocks](SharedConnectionBlock).
+    pub blocked: bool
+}
+
+/// The error returned by [UntypedSignal::emit_any] when the given arguments are not of the signal's argument type.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct EmitAnyError {
+    /// The argument type of the signal.
+    pub expected: &'static str
+}
+
+impl fmt::Display for EmitAnyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "the signal must be emitted with arguments of type {}", self.expected)
+    }
+}
+
+impl Error for EmitAnyError {}
+
+/// An object-safe interface implemented by every [Signal]. Unlike the [Connect](crate::connect) and
+/// [Emit](crate::emit) traits, `UntypedSignal` does not depend on the signal's arguments, return type,
+/// combiner, or group type, which allows signals of different types to be stored in the same collection and
+/// managed together.
+/// # Example
+/// ```
+/// use signals2::*;
+/// use signals2::untyped::UntypedSignal;
+///
+/// let a: Signal<(i32,), i32> = Signal::new();
+/// let b: Signal<(String,)> = Signal::new();
+/// a.connect(|x| x * 2);
+/// b.connect(|s| println!("{}", s));
+///
+/// let signals: Vec<Box<dyn UntypedSignal>> = vec!(Box::new(a.clone()), Box::new(b.clone()));
+/// assert_eq!(signals.iter().map(|sig| sig.count()).sum::<usize>(), 2);
+///
+/// let result = signals[0].emit_any(&(5,)).unwrap();
+/// assert_eq!(result.downcast_ref::<Option<i32>>(), Some(&Some(10)));
+/// assert!(signals[1].emit_any(&(5,)).is_err());
+///
+/// for sig in signals.iter() {
+///     sig.clear();
+/// }
+///
+/// assert_eq!(a.count(), 0);
+/// assert_eq!(b.count(), 0);
+/// ```
+pub trait UntypedSignal: Send + Sync {
+    /// Returns an ID that uniquely identifies the signal. See [Signal::id].
+    fn id(&self) -> u64;
+
+    /// Returns the number of connected slots for the signal.
+    fn count(&self) -> usize;
+
+    /// Disconnects all slots from the signal.
+    fn clear(&self);
+
+    /// Blocks every slot of the signal until the returned [SharedConnectionBlock] is dropped or unblocked.
+    #[must_use="shared connection blocks are automatically unblocked when dropped"]
+    fn block(&self) -> SharedConnectionBlock;
+
+    /// Returns metadata for each connected slot, in the order the slots are executed.
+    fn slots(&self) -> Vec<SlotInfo>;
+
+    /// Emits the signal if `args` is a reference to a tuple of the signal's argument type, returning the output
+    /// of the signal's combiner as a `Box<dyn Any>`. Returns an error without executing any slots otherwise.
+    fn emit_any(&self, args: &dyn Any) -> Result<Box<dyn Any>, EmitAnyError>;
+
+    /// Returns the name of the signal's argument type.
+    fn args_type_name(&self) -> &'static str;
+
+    /// Returns the signal as a `&dyn Any`, which can be used to downcast it back to its concrete type.
+    fn as_any(&self) -> &dyn Any;
+}
+
+impl<Args, R, C, G> UntypedSignal for Signal<Args, R, C, G>
+where
+    Args: Clone + 'static,
+    R: 'static,
+    C: Combiner<R> + 'static,
+    C::Output: 'static,
+    G: Ord + Send + Sync + 'static
+{
+    fn id(&self) -> u64 {
+        Signal::id(self)
+    }
+
+    fn count(&self) -> usize {
+        Signal::count(self)
+    }
+
+    fn clear(&self) {
+        Signal::clear(self);
+    }
+
+    fn block(&self) -> SharedConnectionBlock {
+        self.shared_block(true)
+    }
+
+    fn slots(&self) -> Vec<SlotInfo> {
+        self.snapshot().slot_info()
+    }
+
+    fn emit_any(&self, args: &dyn Any) -> Result<Box<dyn Any>, EmitAnyError> {
+        args.downcast_ref::<Args>()
+            .map(|args| Box::new(self.emit_args(args)) as Box<dyn Any>)
+            .ok_or(EmitAnyError {
+                expected: std::any::type_name::<Args>()
+            })
+    }
+
+    fn args_type_name(&self) -> &'static str {
+        std::any::type_name::<Args>()
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
@@ -0,0 +1,170 @@
+// Copyright Christian Daley 2021
+// Copyright Frank Mori Hess 2007-2008.
+// Distributed under the Boost Software License, Version 1.0. 
+// See http://www.boost.org/LICENSE_1_0.txt
+
+use std::collections::VecDeque;
+use std::ops::Deref;
+use std::sync::{Arc, Mutex, Condvar, mpsc};
+use std::time::{Duration, Instant};
+
+use crate::{Signal, Group, Position, ScopedConnection};
+use crate::combiner::Combiner;
+
+/// The receiving end of a channel created by [Signal::receiver]. Dereferences to the underlying
+/// [Receiver](mpsc::Receiver). The slot that feeds the channel is disconnected as soon as the `SignalReceiver` is
+/// dropped.
+pub struct SignalReceiver<Args> {
+    receiver: mpsc::Receiver<Args>,
+    conn: ScopedConnection
+}
+
+impl<Args> SignalReceiver<Args> {
+    /// Returns the connection of the slot that feeds the channel.
+    pub fn connection(&self) -> &ScopedConnection {
+        &self.conn
+    }
+}
+
+impl<Args> Deref for SignalReceiver<Args> {
+    type Target = mpsc::Receiver<Args>;
+
+    fn deref(&self) -> &mpsc::Receiver<Args> {
+        &self.receiver
+    }
+}
+
+struct WaitState<Args> {
+    queue: VecDeque<Args>,
+    // Set once the waiting slot has been removed from the signal, after which no more emissions can arrive.
+    closed: bool
+}
+
+type SharedWaitState<Args> = Arc<(Mutex<WaitState<Args>>, Condvar)>;
+
+// Owned by the waiting slot. Wakes up the waiting thread when the slot is dropped, which happens when the slot is
+// removed from the signal or the signal itself is dropped.
+struct CloseOnDrop<Args>(SharedWaitState<Args>);
+
+impl<Args> Drop for CloseOnDrop<Args> {
+    fn drop(&mut self) {
+        let (state, cvar) = &*self.0;
+        state.lock().unwrap().closed = true;
+        cvar.notify_all();
+    }
+}
+
+impl<Args, R, C, G> Signal<Args, R, C, G>
+where
+    Args: Clone + 'static,
+    R: 'static,
+    C: Combiner<R> + 'static,
+    G: Ord + Send + Sync + 'static
+{
+    /// Blocks the current thread until the signal is next emitted, returning the arguments the signal was
+    /// emitted with. Returns `None` if the signal is not emitted before `timeout` elapses, or as soon as the signal can
+    /// no longer be emitted to the waiting thread because its slots were [cleared](Signal::clear).
+    ///
+    /// The arguments are captured by a temporary slot connected at the back of [Group::Back]. The slot does not
+    /// contribute to the signal's return value, and it is disconnected before `wait_next` returns.
+    /// # Example
+    /// ```
+    /// use signals2::*;
+    /// use std::thread;
+    /// use std::time::Duration;
+    ///
+    /// let sig: Signal<(i32,)> = Signal::new();
+    /// let emit_handle = sig.get_emit_handle();
+    /// let thread = thread::spawn(move || {
+    ///     thread::sleep(Duration::from_millis(100));
+    ///     emit_handle.emit(5);
+    /// });
+    ///
+    /// assert_eq!(sig.wait_next(Duration::from_secs(5)), Some((5,)));
+    /// assert_eq!(sig.count(), 0);
+    /// thread.join().unwrap();
+    /// ```
+    pub fn wait_next(&self, timeout: Duration) -> Option<Args>
+    where
+        Args: Send
+    {
+        self.wait_until(|_| true, timeout)
+    }
+
+    /// Blocks the current thread until the signal is emitted with arguments for which `pred` returns `true`,
+    /// returning those arguments. Returns `None` if no such emission happens before `timeout` elapses, or as soon as
+    /// the waiting slot is removed from the signal, for example by [clear](Signal::clear). `pred` is evaluated on the
+    /// waiting thread, not on the emitting thread.
+    pub fn wait_until<P>(&self, mut pred: P, timeout: Duration) -> Option<Args>
+    where
+        Args: Send,
+        P: FnMut(&Args) -> bool
+    {
+        let deadline = Instant::now() + timeout;
+        let state: SharedWaitState<Args> = Arc::new((
+            Mutex::new(WaitState { queue: VecDeque::new(), closed: false }),
+            Condvar::new()
+        ));
+        let slot_state = CloseOnDrop(state.clone());
+
+        let _conn = self.connect_partial(move |_, args| {
+            let (state, cvar) = &*slot_state.0;
+            state.lock().unwrap().queue.push_back(args);
+            cvar.notify_all();
+            None
+        }, Group::Back, Position::Back).scoped();
+
+        let (state, cvar) = &*state;
+        let mut lock = state.lock().unwrap();
+        loop {
+            while let Some(args) = lock.queue.pop_front() {
+                if pred(&args) {
+                    return Some(args);
+                }
+            }
+
+            let now = Instant::now();
+            if lock.closed || now >= deadline {
+                return None;
+            }
+
+            lock = cvar.wait_timeout(lock, deadline - now).unwrap().0;
+        }
+    }
+
+    /// Creates a channel that receives the arguments of every subsequent emission of the signal. The channel
+    /// is fed by a slot connected at the back of [Group::Back] that does not contribute to the signal's return value.
+    /// The slot is disconnected when the returned [SignalReceiver] is dropped. If the signal is dropped the channel is
+    /// closed.
+    /// # Example
+    /// ```
+    /// use signals2::*;
+    ///
+    /// let sig: Signal<(i32, i32)> = Signal::new();
+    /// let receiver = sig.receiver();
+    /// sig.emit(1, 2);
+    /// sig.emit(3, 4);
+    /// assert_eq!(receiver.recv(), Ok((1, 2)));
+    /// assert_eq!(receiver.recv(), Ok((3, 4)));
+    ///
+    /// std::mem::drop(receiver);
+    /// assert_eq!(sig.count(), 0);
+    /// ```
+    pub fn receiver(&self) -> SignalReceiver<Args>
+    where
+        Args: Send
+    {
+        let (sender, receiver) = mpsc::channel();
+        let sender = Mutex::new(sender);
+
+        let conn = self.connect_partial(move |_, args| {
+            let _ = sender.lock().unwrap().send(args);
+            None
+        }, Group::Back, Position::Back).scoped();
+
+        SignalReceiver {
+            receiver,
+            conn
+        }
+    }
+}
@@ -0,0 +1,158 @@
+// Copyright Christian Daley 2021
+// Copyright Frank Mori Hess 2007-2008.
+// Distributed under the Boost Software License, Version 1.0. 
+// See http://www.boost.org/LICENSE_1_0.txt
+
+#![cfg(all(feature = "serde", unix))]
+
+use signals2::*;
+use signals2::bridge::{SignalBridge, BridgeReceiver, BridgeError};
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::mem;
+use std::time::{Duration, Instant};
+
+fn socket_path(name: &str) -> PathBuf {
+    let path = std::env::temp_dir().join(format!("signals2-{}-{}.sock", name, std::process::id()));
+    let _ = std::fs::remove_file(&path);
+    path
+}
+
+fn wait_until<F: Fn() -> bool>(f: F) -> bool {
+    let deadline = Instant::now() + Duration::from_secs(5);
+    while Instant::now() < deadline {
+        if f() {
+            return true;
+        }
+
+        thread::sleep(Duration::from_millis(5));
+    }
+
+    false
+}
+
+#[test]
+fn bridge_reconnect_test() {
+    let path = socket_path("reconnect");
+    let local: Signal<(i32, String), i32> = Signal::new();
+    local.connect(|x, _| x);
+    let bridge = SignalBridge::new(&local, &path, 16, Duration::from_millis(10));
+    let errors = Arc::new(Mutex::new(Vec::new()));
+    let errors_clone = errors.clone();
+    bridge.on_error().connect(move |err| errors_clone.lock().unwrap().push(err));
+
+    // emissions are queued until a receiver is listening
+    assert_eq!(local.emit(1, "one".to_string()), Some(1));
+    local.emit(2, "two".to_string());
+
+    let remote: Signal<(i32, String)> = Signal::new();
+    let rx = remote.receiver();
+    let receiver = BridgeReceiver::bind(&path, remote.get_emit_handle()).unwrap();
+    assert_eq!(receiver.path(), path.as_path());
+    assert_eq!(rx.recv_timeout(Duration::from_secs(5)), Ok((1, "one".to_string())));
+    assert_eq!(rx.recv_timeout(Duration::from_secs(5)), Ok((2, "two".to_string())));
+
+    mem::drop(receiver);
+    assert!(!path.exists());
+
+    // the lost connection is reported once a send fails, and the emission is sent again after reconnecting
+    local.emit(3, "three".to_string());
+    assert!(wait_until(|| !errors.lock().unwrap().is_empty()));
+    assert!(matches!(errors.lock().unwrap()[0], BridgeError::Disconnected(_)));
+
+    let receiver = BridgeReceiver::bind(&path, remote.get_emit_handle()).unwrap();
+    assert_eq!(rx.recv_timeout(Duration::from_secs(5)), Ok((3, "three".to_string())));
+
+    mem::drop(bridge);
+    assert_eq!(local.count(), 1);
+    mem::drop(receiver);
+}
+
+#[test]
+fn bridge_queue_full_test() {
+    let path = socket_path("queue-full");
+    let local: Signal<(i32,)> = Signal::new();
+    let bridge = SignalBridge::new(&local, &path, 1, Duration::from_millis(10));
+    let errors = Arc::new(Mutex::new(Vec::new()));
+    let errors_clone = errors.clone();
+    bridge.on_error().connect(move |err| errors_clone.lock().unwrap().push(err));
+
+    for i in 0..3 {
+        local.emit(i);
+    }
+
+    assert!(errors.lock().unwrap().contains(&BridgeError::QueueFull));
+
+    // dropping the bridge does not wait for a receiver that never shows up
+    mem::drop(bridge);
+    assert_eq!(local.count(), 0);
+}
+
+#[test]
+fn bridge_receiver_prunes_connections_test() {
+    let path = socket_path("prune");
+    let remote: Signal<(i32,)> = Signal::new();
+    let rx = remote.receiver();
+    let receiver = BridgeReceiver::bind(&path, remote.get_emit_handle()).unwrap();
+    let local: Signal<(i32,)> = Signal::new();
+
+    for i in 0..3 {
+        let bridge = SignalBridge::new(&local, &path, 16, Duration::from_millis(10));
+        local.emit(i);
+        assert_eq!(rx.recv_timeout(Duration::from_secs(5)), Ok((i,)));
+        assert_eq!(receiver.connection_count(), 1);
+
+        // the connection is forgotten once the bridge goes away
+        mem::drop(bridge);
+        assert!(wait_until(|| receiver.connection_count() == 0));
+    }
+}
+
+#[test]
+fn bridge_drop_with_stalled_receiver_test() {
+    let path = socket_path("stalled");
+    let listener = std::os::unix::net::UnixListener::bind(&path).unwrap();
+    let local: Signal<(Vec<u8>,)> = Signal::new();
+    let bridge = SignalBridge::new(&local, &path, 16, Duration::from_millis(10));
+
+    for _ in 0..4 {
+        local.emit(vec!(0; 1 << 20));
+    }
+
+    // accept the connection but never read from it, so the bridge's writes eventually block
+    let (_stream, _) = listener.accept().unwrap();
+
+    let (done_tx, done_rx) = std::sync::mpsc::channel();
+    thread::spawn(move || {
+        mem::drop(bridge);
+        done_tx.send(()).unwrap();
+    });
+
+    assert_eq!(done_rx.recv_timeout(Duration::from_secs(10)), Ok(()));
+    let _ = std::fs::remove_file(&path);
+}
+
+#[test]
+fn bridge_receiver_drop_without_socket_file_test() {
+    let path = socket_path("unlinked");
+    let remote: Signal<(i32,)> = Signal::new();
+    let receiver = BridgeReceiver::bind(&path, remote.get_emit_handle()).unwrap();
+    std::fs::remove_file(&path).unwrap();
+
+    // the receiver shuts down even though nothing can connect to it anymore
+    let (done_tx, done_rx) = std::sync::mpsc::channel();
+    thread::spawn(move || {
+        mem::drop(receiver);
+        done_tx.send(()).unwrap();
+    });
+
+    assert_eq!(done_rx.recv_timeout(Duration::from_secs(10)), Ok(()));
+}
+
+#[test]
+#[should_panic]
+fn bridge_zero_capacity_test() {
+    let local: Signal<(i32,)> = Signal::new();
+    let _bridge = SignalBridge::new(&local, socket_path("zero-capacity"), 0, Duration::from_millis(10));
+}
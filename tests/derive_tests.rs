@@ -0,0 +1,67 @@
+// Copyright Christian Daley 2021
+// Copyright Frank Mori Hess 2007-2008.
+// Distributed under the Boost Software License, Version 1.0. 
+// See http://www.boost.org/LICENSE_1_0.txt
+
+#![cfg(feature = "derive")]
+
+use std::sync::{Arc, atomic::{AtomicUsize, Ordering}};
+
+use signals2::*;
+use signals2::combiner::Combiner;
+
+#[derive(Default)]
+struct SumCombiner {}
+
+impl Combiner<usize> for SumCombiner {
+    type Output = usize;
+
+    fn combine(&self, iter: impl Iterator<Item=usize>) -> usize {
+        iter.sum()
+    }
+}
+
+#[derive(Default, Signals)]
+struct Widget {
+    #[signal]
+    resized: signal!(fn(u32, u32)),
+    #[signal]
+    closing: Signal<(), bool>,
+    #[signal]
+    renamed: signal!(fn(String) -> usize, SumCombiner, u8),
+    #[signal]
+    moved: Signal<Point>,
+    _name: String
+}
+
+type Point = (i32, i32);
+
+#[derive(Signals)]
+struct GenericWidget<T: Clone + Send + Sync + 'static> {
+    #[signal]
+    changed: Signal<(T,)>,
+}
+
+#[test]
+fn derive_signals_test() {
+    let widget = Widget::default();
+    let count = Arc::new(AtomicUsize::new(0));
+
+    let count_clone = count.clone();
+    widget.on_resized().connect(move |w, h| { count_clone.fetch_add((w * h) as usize, Ordering::SeqCst); });
+    widget.on_closing().connect(|| false);
+    widget.on_renamed().connect_group(|name| name.len(), Group::Named(1));
+    widget.on_renamed().connect_group(|_| 1, Group::Named(0));
+    let count_clone = count.clone();
+    widget.on_moved().connect_tuple(move |(x, y)| { count_clone.fetch_add((x + y) as usize, Ordering::SeqCst); });
+
+    widget.emit_resized(2, 3);
+    widget.emit_moved((4, 5));
+    assert_eq!(count.load(Ordering::SeqCst), 15);
+    assert_eq!(widget.emit_closing(), Some(false));
+    assert_eq!(widget.emit_renamed("abc".to_string()), 4);
+
+    let generic = GenericWidget { changed: Signal::new() };
+    generic.on_changed().connect(|s: &'static str| assert_eq!(s, "x"));
+    generic.emit_changed("x");
+}
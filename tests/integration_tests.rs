@@ -4,10 +4,20 @@
 // See http://www.boost.org/LICENSE_1_0.txt
 
 use signals2::*;
+use signals2::replay::{BehaviorSignal, ReplaySignal};
+use signals2::property::Property;
+use signals2::computed::Computed;
+use signals2::collections::{ObservableVec, VecChange, ObservableMap, MapChange};
+use signals2::event_bus::EventBus;
+use signals2::registry::{SignalRegistry, RegistryError};
+use signals2::untyped::{UntypedSignal, SlotInfo, SlotGroup};
+use signals2::testing::{SignalSpy, MockSlot, Sequence};
+use signals2::reentrancy::{ReentrancyPolicy, ReentrancyError};
+use signals2::context::EmissionContext;
 use combiner::{Combiner, VecCombiner, SumCombiner};
 use std::thread;
 use std::mem;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 use std::sync::{Arc, atomic::{AtomicUsize, Ordering}};
 
 #[test]
@@ -376,7 +386,7 @@ fn lazy_slots_test() {
     sig.connect(move || { counter_clone5.fetch_add(1, Ordering::Relaxed); 4 });
 
 
-    assert_eq!(sig.emit(), vec!());
+    assert_eq!(sig.emit(), Vec::<i32>::new());
     assert_eq!(counter.load(Ordering::Relaxed), 0);
 
     sig.set_combiner(FirstNSlots {n: 1});
@@ -587,4 +597,1314 @@ fn default_test() {
     assert_eq!(sig.emit(), None);
     sig.connect(|| 5);
     assert_eq!(sig.emit(), Some(5));
-}
\ No newline at end of file
+}
+
+#[test]
+fn emit_parallel_test() {
+    let sig: Signal<(i32,), i32, VecCombiner> = Signal::new();
+    assert_eq!(sig.emit_parallel(0), Vec::<i32>::new());
+
+    for i in 0..8 {
+        sig.connect_group(move |x| x + i, Group::Named(-i));
+    }
+
+    let blocked = sig.connect(|x| x * 100);
+    let _block = blocked.shared_block(true);
+    assert_eq!(sig.emit_parallel(10), vec!(17, 16, 15, 14, 13, 12, 11, 10));
+
+    // both slots must be running at the same time to get past the barrier. rayon's pool may have a single thread.
+    #[cfg(not(feature = "rayon"))]
+    {
+        let pair: Signal<(), i32, VecCombiner> = Signal::new();
+        let barrier = Arc::new(std::sync::Barrier::new(2));
+        for i in 0..2 {
+            let barrier = barrier.clone();
+            pair.connect(move || {
+                barrier.wait();
+                i
+            });
+        }
+
+        assert_eq!(pair.emit_parallel(), vec!(0, 1));
+    }
+
+    // a panicking slot does not leave the other slots running after the emission returns
+    let panicking: Signal<()> = Signal::new();
+    let finished = Arc::new(AtomicUsize::new(0));
+    panicking.connect(|| panic!("slot panicked"));
+    for _ in 0..4 {
+        let finished = finished.clone();
+        panicking.connect(move || {
+            thread::sleep(Duration::from_millis(50));
+            finished.fetch_add(1, Ordering::SeqCst);
+        });
+    }
+
+    assert!(std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| panicking.emit_parallel())).is_err());
+    let after = finished.load(Ordering::SeqCst);
+    thread::sleep(Duration::from_millis(200));
+    assert_eq!(finished.load(Ordering::SeqCst), after);
+}
+
+#[test]
+fn emit_parallel_grouped_test() {
+    let sig: Signal<(), i32, VecCombiner> = Signal::new();
+    let finished = Arc::new(AtomicUsize::new(0));
+
+    for group in 0..3 {
+        for _ in 0..3 {
+            let finished = finished.clone();
+            sig.connect_group(move || {
+                // every slot from the previous groups must have finished before this group starts
+                assert!(finished.load(Ordering::SeqCst) >= 3 * group as usize);
+                thread::sleep(Duration::from_millis(100));
+                finished.fetch_add(1, Ordering::SeqCst);
+                group
+            }, Group::Named(group));
+        }
+    }
+
+    assert_eq!(sig.emit_parallel_grouped(), vec!(0, 0, 0, 1, 1, 1, 2, 2, 2));
+    assert_eq!(finished.load(Ordering::SeqCst), 9);
+
+    let emit_handle = sig.get_emit_handle();
+    finished.store(0, Ordering::SeqCst);
+    assert_eq!(emit_handle.emit_parallel_grouped(), Some(vec!(0, 0, 0, 1, 1, 1, 2, 2, 2)));
+}
+
+#[test]
+fn wait_next_test() {
+    let sig: Signal<(i32,), i32> = Signal::new();
+    sig.connect(|x| x * 2);
+
+    assert_eq!(sig.wait_next(Duration::from_millis(100)), None);
+    assert_eq!(sig.count(), 1);
+
+    let emit_handle = sig.get_emit_handle();
+    let thread = thread::spawn(move || {
+        for i in 0..10 {
+            thread::sleep(Duration::from_millis(20));
+            // waiting slots do not change the value returned by the combiner
+            assert_eq!(emit_handle.emit(i), Some(Some(i * 2)));
+        }
+    });
+
+    assert_eq!(sig.wait_until(|(x,)| *x >= 5, Duration::from_secs(5)), Some((5,)));
+    assert_eq!(sig.count(), 1);
+    thread.join().unwrap();
+
+    // clearing the signal wakes up the waiting thread instead of leaving it waiting for the full timeout
+    let clearing = sig.clone();
+    let thread = thread::spawn(move || {
+        while clearing.count() < 2 {
+            thread::yield_now();
+        }
+
+        clearing.clear();
+    });
+
+    let start = Instant::now();
+    assert_eq!(sig.wait_next(Duration::from_secs(60)), None);
+    assert!(start.elapsed() < Duration::from_secs(30));
+    thread.join().unwrap();
+}
+
+#[test]
+fn receiver_test() {
+    let sig: Signal<(i32, String)> = Signal::new();
+    let receiver = sig.receiver();
+    assert_eq!(sig.count(), 1);
+
+    sig.emit(1, "a".to_string());
+    sig.emit(2, "b".to_string());
+    assert_eq!(receiver.try_recv(), Ok((1, "a".to_string())));
+    assert_eq!(receiver.try_recv(), Ok((2, "b".to_string())));
+    assert!(receiver.try_recv().is_err());
+
+    let receiver2 = sig.receiver();
+    mem::drop(receiver);
+    assert_eq!(sig.count(), 1);
+    assert!(receiver2.connection().connected());
+    sig.emit(3, "c".to_string());
+    assert_eq!(receiver2.try_recv(), Ok((3, "c".to_string())));
+
+    mem::drop(sig);
+    assert!(receiver2.recv().is_err());
+}
+
+#[test]
+fn behavior_signal_test() {
+    let sig: BehaviorSignal<(i32,), i32, VecCombiner> = BehaviorSignal::new();
+    let counter = Arc::new(AtomicUsize::new(0));
+
+    let counter_clone = counter.clone();
+    sig.connect(move |x| {
+        counter_clone.fetch_add(x as usize, Ordering::SeqCst);
+        x
+    });
+
+    // no value yet, so nothing is replayed
+    assert_eq!(counter.load(Ordering::SeqCst), 0);
+    assert_eq!(sig.value(), None);
+    assert_eq!(sig.emit(2), vec!(2));
+    assert_eq!(counter.load(Ordering::SeqCst), 2);
+
+    let counter_clone = counter.clone();
+    sig.connect_group(move |x| {
+        counter_clone.fetch_add(10 * x as usize, Ordering::SeqCst);
+        -x
+    }, Group::Front);
+
+    assert_eq!(counter.load(Ordering::SeqCst), 22);
+    assert_eq!(sig.emit(3), vec!(-3, 3));
+    assert_eq!(sig.value(), Some((3,)));
+
+    let replayed = Arc::new(AtomicUsize::new(0));
+    let replayed_clone = replayed.clone();
+    let conn = sig.connect_extended(move |conn, x| {
+        assert!(conn.connected());
+        replayed_clone.store(x as usize, Ordering::SeqCst);
+        0
+    });
+
+    assert_eq!(replayed.load(Ordering::SeqCst), 3);
+    assert_eq!(sig.count(), 3);
+    conn.disconnect();
+
+    sig.reset();
+    assert_eq!(sig.value(), None);
+    let initial: BehaviorSignal<(i32, i32), i32> = BehaviorSignal::with_value((4, 5));
+    let sum = Arc::new(AtomicUsize::new(0));
+    let sum_clone = sum.clone();
+    initial.connect(move |x, y| sum_clone.fetch_add((x + y) as usize, Ordering::SeqCst) as i32);
+    assert_eq!(sum.load(Ordering::SeqCst), 9);
+}
+
+#[test]
+fn replay_signal_test() {
+    let sig: ReplaySignal<(i32,)> = ReplaySignal::new(3);
+    assert_eq!(sig.capacity(), 3);
+
+    for i in 0..5 {
+        sig.emit(i);
+    }
+
+    assert_eq!(sig.history(), vec!((2,), (3,), (4,)));
+
+    let received = sig.clone();
+    let (sender, receiver) = std::sync::mpsc::channel();
+    let sender = std::sync::Mutex::new(sender);
+    received.connect(move |x| sender.lock().unwrap().send(x).unwrap());
+    sig.emit(5);
+
+    assert_eq!(receiver.try_iter().collect::<Vec<i32>>(), vec!(2, 3, 4, 5));
+    assert_eq!(sig.history(), vec!((3,), (4,), (5,)));
+
+    let empty: ReplaySignal<()> = ReplaySignal::new(0);
+    empty.emit();
+    assert!(empty.history().is_empty());
+
+    // a slot may connect to and emit the signal while it is being replayed
+    let reentrant: ReplaySignal<(i32,)> = ReplaySignal::new(2);
+    reentrant.emit(1);
+    let inner = reentrant.clone();
+    let seen = Arc::new(std::sync::Mutex::new(Vec::new()));
+    let seen_clone = seen.clone();
+    let conn = reentrant.connect(move |x| {
+        seen_clone.lock().unwrap().push(x);
+        if x == 1 {
+            inner.connect(|_| ());
+            inner.emit(2);
+        }
+    });
+
+    conn.disconnect();
+    assert_eq!(*seen.lock().unwrap(), vec!(1, 2));
+    assert_eq!(reentrant.history(), vec!((1,), (2,)));
+    assert_eq!(reentrant.count(), 1);
+
+    // a slot that blocks itself while it is being replayed misses the rest of the replay
+    let blocks = Arc::new(std::sync::Mutex::new(Vec::new()));
+    let blocks_clone = blocks.clone();
+    let replayed = Arc::new(std::sync::Mutex::new(Vec::new()));
+    let replayed_clone = replayed.clone();
+    let conn = sig.connect_extended(move |conn, x| {
+        replayed_clone.lock().unwrap().push(x);
+        blocks_clone.lock().unwrap().push(conn.shared_block(true));
+    });
+
+    assert_eq!(*replayed.lock().unwrap(), vec!(3));
+    blocks.lock().unwrap().clear();
+    assert!(!conn.blocked());
+}
+
+#[test]
+fn replay_concurrent_emit_test() {
+    // slots connected while another thread emits never see a replayed value after a newer one
+    let sig: BehaviorSignal<(i32,)> = BehaviorSignal::with_value((0,));
+    let emitter = sig.clone();
+    let handle = thread::spawn(move || {
+        for i in 1..=1000 {
+            emitter.emit(i);
+        }
+    });
+
+    let mut subscribers = Vec::new();
+    for _ in 0..200 {
+        let seen = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let seen_clone = seen.clone();
+        sig.connect(move |x| seen_clone.lock().unwrap().push(x));
+        subscribers.push(seen);
+    }
+
+    handle.join().unwrap();
+    for seen in subscribers {
+        let seen = seen.lock().unwrap();
+        assert_eq!(seen.last(), Some(&1000));
+        assert!(seen.windows(2).all(|pair| pair[0] < pair[1]));
+    }
+}
+
+#[test]
+fn property_test() {
+    let prop = Property::new(String::from("a"));
+    let changes = Arc::new(std::sync::Mutex::new(Vec::new()));
+    let changes_clone = changes.clone();
+    prop.changed().connect(move |old, new| changes_clone.lock().unwrap().push((old, new)));
+
+    assert!(prop.set("b".to_string()));
+    assert!(!prop.set("b".to_string()));
+    assert!(prop.modify(|s| s.push('c')));
+    assert!(!prop.modify(|_| ()));
+    assert_eq!(prop.get(), "bc");
+
+    assert_eq!(*changes.lock().unwrap(), vec!(
+        ("a".to_string(), "b".to_string()),
+        ("b".to_string(), "bc".to_string())
+    ));
+}
+
+#[test]
+fn property_binding_test() {
+    let a = Property::new(1);
+    let b = Property::new(2);
+    let c = Property::new(3);
+
+    let counter = Arc::new(AtomicUsize::new(0));
+    let counter_clone = counter.clone();
+    a.changed().connect(move |_, _| { counter_clone.fetch_add(1, Ordering::SeqCst); });
+
+    {
+        let _ab = a.bind_bidirectional(&b);
+        let _ca = c.bind(&a);
+        assert_eq!((a.get(), b.get(), c.get()), (2, 2, 2));
+
+        b.set(5);
+        assert_eq!((a.get(), b.get(), c.get()), (5, 5, 5));
+
+        a.set(6);
+        assert_eq!((a.get(), b.get(), c.get()), (6, 6, 6));
+
+        // one-way binding, changing the target does not affect the source
+        c.set(7);
+        assert_eq!((a.get(), b.get(), c.get()), (6, 6, 7));
+        assert_eq!(counter.load(Ordering::SeqCst), 3);
+    }
+
+    b.set(8);
+    assert_eq!((a.get(), b.get(), c.get()), (6, 8, 7));
+
+    // bindings do not keep their properties alive
+    let binding = c.bind(&a);
+    mem::drop(c);
+    a.set(9);
+    binding.unbind();
+}
+
+#[test]
+fn computed_test() {
+    let evaluations = Arc::new(AtomicUsize::new(0));
+    let use_a = Property::new(true);
+    let a = Property::new(1);
+    let b = Property::new(10);
+
+    let sum = {
+        let (use_a, a, b, evaluations) = (use_a.clone(), a.clone(), b.clone(), evaluations.clone());
+        Computed::new(move || {
+            evaluations.fetch_add(1, Ordering::SeqCst);
+            if use_a.get() { a.get() + b.get() } else { b.get() }
+        })
+    };
+
+    assert_eq!(evaluations.load(Ordering::SeqCst), 0);
+    assert_eq!(sum.get(), 11);
+    assert_eq!(sum.get(), 11);
+    assert_eq!(evaluations.load(Ordering::SeqCst), 1);
+    assert_eq!(sum.dependency_count(), 3);
+
+    // nobody is observing the computed value, so it is recomputed lazily
+    a.set(2);
+    a.set(3);
+    assert_eq!(evaluations.load(Ordering::SeqCst), 1);
+    assert_eq!(sum.get(), 13);
+    assert_eq!(evaluations.load(Ordering::SeqCst), 2);
+
+    use_a.set(false);
+    assert_eq!(sum.get(), 10);
+    assert_eq!(sum.dependency_count(), 2);
+
+    // `a` is no longer a dependency
+    a.set(4);
+    assert_eq!(sum.get(), 10);
+    assert_eq!(evaluations.load(Ordering::SeqCst), 3);
+}
+
+#[test]
+fn computed_changed_test() {
+    let x = Property::new(2);
+    let parity = {
+        let x = x.clone();
+        Computed::new(move || x.get() % 2)
+    };
+    let description = {
+        let parity = parity.clone();
+        Computed::new(move || if parity.get() == 0 { "even" } else { "odd" })
+    };
+
+    let changes = Arc::new(std::sync::Mutex::new(Vec::new()));
+    let changes_clone = changes.clone();
+    description.changed().connect(move |old, new| changes_clone.lock().unwrap().push((old, new)));
+
+    x.set(4);
+    x.set(5);
+    x.set(7);
+    x.set(8);
+    assert_eq!(*changes.lock().unwrap(), vec!(("even", "odd"), ("odd", "even")));
+
+    mem::drop(description);
+    mem::drop(parity);
+    x.set(9);
+    assert_eq!(changes.lock().unwrap().len(), 2);
+}
+
+#[test]
+fn observable_vec_test() {
+    let vec: ObservableVec<i32> = ObservableVec::from(vec!(1, 2));
+    let changes = Arc::new(std::sync::Mutex::new(Vec::new()));
+    let changes_clone = changes.clone();
+    vec.changed().connect(move |change| changes_clone.lock().unwrap().push(change));
+
+    vec.push(3);
+    vec.insert(0, 0);
+    assert_eq!(vec.remove(1), 1);
+    assert_eq!(vec.replace(0, 5), 0);
+    vec.move_item(2, 0);
+    assert_eq!(vec.pop(), Some(2));
+    assert_eq!(vec.to_vec(), vec!(3, 5));
+    vec.clear();
+    vec.clear();
+    assert_eq!(vec.pop(), None);
+    assert!(vec.is_empty());
+
+    assert_eq!(*changes.lock().unwrap(), vec!(
+        VecChange::Insert { index: 2, value: 3 },
+        VecChange::Insert { index: 0, value: 0 },
+        VecChange::Remove { index: 1, value: 1 },
+        VecChange::Replace { index: 0, old: 0, new: 5 },
+        VecChange::Move { from: 2, to: 0 },
+        VecChange::Remove { index: 2, value: 2 },
+        VecChange::Clear { values: vec!(3, 5) }
+    ));
+
+    changes.lock().unwrap().clear();
+    let len = vec.transaction(|tx| {
+        tx.push(1);
+        tx.push(2);
+        tx.replace(1, 3);
+        tx.as_slice().len()
+    });
+
+    vec.transaction(|_| ());
+    assert_eq!(len, 2);
+    assert_eq!(vec.len(), 2);
+    assert_eq!(vec.get(1), Some(3));
+    assert_eq!(*changes.lock().unwrap(), vec!(VecChange::Batch(vec!(
+        VecChange::Insert { index: 0, value: 1 },
+        VecChange::Insert { index: 1, value: 2 },
+        VecChange::Replace { index: 1, old: 2, new: 3 }
+    ))));
+
+    // moving an item onto itself changes nothing, and an out of range move leaves the vector untouched
+    changes.lock().unwrap().clear();
+    vec.move_item(1, 1);
+    let moved = vec.clone();
+    assert!(thread::spawn(move || moved.move_item(0, 2)).join().is_err());
+    let moved = vec.clone();
+    assert!(thread::spawn(move || moved.transaction(|tx| tx.move_item(2, 0))).join().is_err());
+    assert_eq!(vec.to_vec(), vec!(1, 3));
+    assert!(changes.lock().unwrap().is_empty());
+}
+
+#[test]
+fn observable_map_test() {
+    let map: ObservableMap<String, i32> = ObservableMap::new();
+    let changes = Arc::new(std::sync::Mutex::new(Vec::new()));
+    let changes_clone = changes.clone();
+    map.changed().connect(move |change| changes_clone.lock().unwrap().push(change));
+
+    assert_eq!(map.insert("a".to_string(), 1), None);
+    assert_eq!(map.insert("a".to_string(), 2), Some(1));
+    assert_eq!(map.remove(&"b".to_string()), None);
+    assert_eq!(map.remove(&"a".to_string()), Some(2));
+
+    map.transaction(|tx| {
+        tx.insert("x".to_string(), 10);
+        tx.insert("y".to_string(), 20);
+        tx.remove(&"x".to_string());
+    });
+
+    assert!(map.contains_key(&"y".to_string()));
+    assert_eq!(map.len(), 1);
+    map.clear();
+    map.clear();
+
+    assert_eq!(*changes.lock().unwrap(), vec!(
+        MapChange::Insert { key: "a".to_string(), value: 1 },
+        MapChange::Replace { key: "a".to_string(), old: 1, new: 2 },
+        MapChange::Remove { key: "a".to_string(), value: 2 },
+        MapChange::Batch(vec!(
+            MapChange::Insert { key: "x".to_string(), value: 10 },
+            MapChange::Insert { key: "y".to_string(), value: 20 },
+            MapChange::Remove { key: "x".to_string(), value: 10 }
+        )),
+        MapChange::Clear { entries: vec!(("y".to_string(), 20)) }
+    ));
+}
+
+#[test]
+fn observable_collection_consistency_test() {
+    // a listener that replays every change must end up with the same contents as the vector
+    let vec: ObservableVec<usize> = ObservableVec::new();
+    let mirror = Arc::new(std::sync::Mutex::new(Vec::new()));
+    let mirror_clone = mirror.clone();
+    vec.changed().connect(move |change| {
+        let mut mirror = mirror_clone.lock().unwrap();
+        match change {
+            VecChange::Insert { index, value } => mirror.insert(index, value),
+            VecChange::Remove { index, .. } => { mirror.remove(index); },
+            VecChange::Batch(changes) => for change in changes {
+                if let VecChange::Insert { index, value } = change {
+                    mirror.insert(index, value);
+                }
+            },
+            change => panic!("unexpected change {:?}", change)
+        }
+    });
+
+    let threads: Vec<thread::JoinHandle<()>> = (0..4)
+        .map(|t| {
+            let vec = vec.clone();
+            thread::spawn(move || {
+                for i in 0..100 {
+                    vec.insert(0, t * 100 + i);
+                    if i % 3 == 0 {
+                        vec.pop();
+                    }
+                }
+            })
+        })
+        .collect();
+
+    for thread in threads {
+        thread.join().unwrap();
+    }
+
+    assert_eq!(*mirror.lock().unwrap(), vec.to_vec());
+
+    // a panic does not poison the vector, and the changes made before it are emitted
+    assert!(std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| vec.remove(1000))).is_err());
+    let len = vec.len();
+    assert!(std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| vec.transaction(|tx| {
+        tx.push(1);
+        tx.remove(1000);
+    }))).is_err());
+    assert_eq!(vec.len(), len + 1);
+    assert_eq!(*mirror.lock().unwrap(), vec.to_vec());
+
+    // a listener may mutate the collection whose change it is handling
+    let map: ObservableMap<i32, i32> = ObservableMap::new();
+    let inner = map.clone();
+    map.changed().connect(move |change| {
+        if let MapChange::Insert { key, value } = change {
+            if key < 3 {
+                inner.insert(key + 1, value);
+            }
+        }
+    });
+
+    map.insert(0, 7);
+    assert_eq!(map.len(), 4);
+    assert!(std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| map.transaction(|_| panic!("failed")))).is_err());
+    assert_eq!(map.get(&3), Some(7));
+}
+
+#[test]
+fn event_bus_test() {
+    #[derive(Clone, Debug, PartialEq)]
+    struct Opened(String);
+
+    #[derive(Clone, Debug, PartialEq)]
+    struct Closed(String);
+
+    let bus: EventBus = EventBus::new();
+    let log = Arc::new(std::sync::Mutex::new(Vec::new()));
+
+    let log_clone = log.clone();
+    bus.subscribe(move |Opened(name)| log_clone.lock().unwrap().push(format!("opened {}", name)));
+    let log_clone = log.clone();
+    bus.subscribe_group(move |Opened(name)| log_clone.lock().unwrap().push(format!("first {}", name)), Group::Front);
+    let log_clone = log.clone();
+    let closed_conn = bus.subscribe(move |Closed(name)| log_clone.lock().unwrap().push(format!("closed {}", name)));
+
+    assert_eq!(bus.subscriber_count::<Opened>(), 2);
+    assert_eq!(bus.subscriber_count::<Closed>(), 1);
+    assert_eq!(bus.subscriber_count::<i32>(), 0);
+
+    bus.publish(Opened("a".to_string()));
+    bus.publish(Closed("a".to_string()));
+    bus.publish(7i32);
+    closed_conn.disconnect();
+    bus.publish(Closed("b".to_string()));
+
+    assert_eq!(*log.lock().unwrap(), vec!("first a", "opened a", "closed a"));
+
+    let publish_handle = bus.get_publish_handle();
+    let subscribe_handle = bus.get_subscribe_handle();
+    let counter = Arc::new(AtomicUsize::new(0));
+    let counter_clone = counter.clone();
+    let conn = subscribe_handle.subscribe(move |x: usize| { counter_clone.fetch_add(x, Ordering::SeqCst); });
+
+    let thread = thread::spawn(move || {
+        for _ in 0..100 {
+            assert!(publish_handle.publish(2usize));
+        }
+
+        publish_handle
+    });
+
+    let publish_handle = thread.join().unwrap();
+    assert_eq!(counter.load(Ordering::SeqCst), 200);
+
+    mem::drop(bus);
+    assert!(!conn.connected());
+    assert!(!publish_handle.publish(1usize));
+    assert!(!subscribe_handle.subscribe(|_: usize| ()).connected());
+}
+
+#[test]
+fn signal_registry_test() {
+    let registry = SignalRegistry::new();
+    let buffer_saved = registry.register::<(i32,)>("editor/buffer/saved").unwrap();
+    let buffer_closed = registry.register::<(i32,)>("editor/buffer/closed").unwrap();
+    let opened = registry.register::<(String,)>("editor/opened").unwrap();
+
+    assert_eq!(registry.register::<()>("editor//saved").err(), Some(RegistryError::InvalidPath("editor//saved".to_string())));
+    assert_eq!(registry.register::<()>("editor/*").err(), Some(RegistryError::InvalidPath("editor/*".to_string())));
+    assert!(matches!(registry.register::<(String,)>("editor/buffer/saved"), Err(RegistryError::TypeMismatch { .. })));
+    assert!(matches!(registry.get::<(String,)>("editor/buffer/saved"), Err(RegistryError::TypeMismatch { .. })));
+    assert_eq!(registry.get::<(i32,)>("editor/missing").err(), Some(RegistryError::NotFound("editor/missing".to_string())));
+    assert!(matches!(registry.subscribe("editor/#/saved", |_, _: ()| ()), Err(RegistryError::InvalidPattern(_))));
+    assert!(matches!(registry.subscribe("editor/sa*", |_, _: ()| ()), Err(RegistryError::InvalidPattern(_))));
+
+    let again = registry.register::<(i32,)>("editor/buffer/saved").unwrap();
+    again.connect(|_| ());
+    assert_eq!(buffer_saved.count(), 1);
+    buffer_saved.clear();
+
+    let log = Arc::new(std::sync::Mutex::new(Vec::new()));
+    let log_clone = log.clone();
+    let star = registry.subscribe("editor/*/saved", move |path, (x,): (i32,)| {
+        log_clone.lock().unwrap().push(format!("* {} {}", path, x));
+    }).unwrap();
+
+    let log_clone = log.clone();
+    let hash = registry.subscribe("editor/#", move |path, (x,): (i32,)| {
+        log_clone.lock().unwrap().push(format!("# {} {}", path, x));
+    }).unwrap();
+
+    assert_eq!(buffer_saved.count(), 2);
+    assert_eq!(buffer_closed.count(), 1);
+    assert_eq!(opened.count(), 0);
+
+    let tab_saved = registry.register::<(i32,)>("editor/tab/saved").unwrap();
+    let editor = registry.register::<(i32,)>("editor").unwrap();
+    assert_eq!(tab_saved.count(), 2);
+    assert_eq!(editor.count(), 1);
+    assert_eq!(registry.paths("editor/*/saved").unwrap(), vec!("editor/buffer/saved", "editor/tab/saved"));
+
+    buffer_saved.emit(1);
+    buffer_closed.emit(2);
+    tab_saved.emit(3);
+    opened.emit("x".to_string());
+    assert_eq!(*log.lock().unwrap(), vec!(
+        "* editor/buffer/saved 1",
+        "# editor/buffer/saved 1",
+        "# editor/buffer/closed 2",
+        "* editor/tab/saved 3",
+        "# editor/tab/saved 3"
+    ));
+
+    star.unsubscribe();
+    assert_eq!(buffer_saved.count(), 1);
+    mem::drop(hash);
+    assert_eq!(buffer_saved.count(), 0);
+    registry.register::<(i32,)>("editor/view/saved").unwrap();
+    assert_eq!(registry.get::<(i32,)>("editor/view/saved").unwrap().count(), 0);
+
+    assert!(registry.unregister("editor"));
+    assert!(!registry.unregister("editor"));
+    assert!(!registry.contains("editor"));
+}
+
+#[test]
+fn untyped_signal_test() {
+    let sig1: Signal<(i32,), i32> = Signal::new();
+    let sig2: Signal<(String, usize), (), signals2::combiner::DefaultCombiner, usize> = Signal::new();
+    let front = sig1.connect_group(|x| x + 1, Group::Front);
+    let back = sig1.connect(|x| x * 2);
+    let conn = sig2.connect_group(|_, _| (), Group::Named(3));
+    let _blocker = conn.shared_block(true);
+
+    let signals: Vec<Box<dyn UntypedSignal>> = vec!(Box::new(sig1.clone()), Box::new(sig2.clone()));
+    assert_eq!(signals[0].count(), 2);
+    assert_eq!(signals[1].count(), 1);
+    assert_eq!(signals[0].slots(), vec!(
+        SlotInfo { id: front.id(), group: SlotGroup::Front, blocked: false },
+        SlotInfo { id: back.id(), group: SlotGroup::Back, blocked: false }
+    ));
+    assert_eq!(signals[1].slots(), vec!(SlotInfo { id: conn.id(), group: SlotGroup::Named, blocked: true }));
+
+    let output = signals[0].emit_any(&(4,)).unwrap();
+    assert_eq!(*output.downcast::<Option<i32>>().unwrap(), Some(8));
+    let err = signals[0].emit_any(&4).err().unwrap();
+    assert_eq!(err.expected, signals[0].args_type_name());
+    assert!(signals[1].emit_any(&("a".to_string(), 1usize)).is_ok());
+
+    let blockers: Vec<SharedConnectionBlock> = signals.iter().map(|sig| sig.block()).collect();
+    assert!(sig1.blocked());
+    assert_eq!(sig1.emit(4), None);
+    sig1.connect(|x| x * 3);
+    assert_eq!(sig1.emit(4), None);
+    mem::drop(blockers);
+    assert!(!sig1.blocked());
+    assert_eq!(sig1.emit(4), Some(12));
+
+    assert!(signals[0].as_any().downcast_ref::<Signal<(i32,), i32>>().is_some());
+    for sig in signals.iter() {
+        sig.clear();
+    }
+
+    assert_eq!(sig1.count(), 0);
+    assert_eq!(sig2.count(), 0);
+}
+
+#[test]
+fn tuple_traits_test() {
+    fn connect_counter<S, Args>(sig: &S, counter: Arc<AtomicUsize>) -> Connection
+    where
+        S: Connect<Args, Return = usize>,
+        Args: Clone + 'static
+    {
+        sig.connect_tuple(move |_| counter.fetch_add(1, Ordering::SeqCst) + 1)
+    }
+
+    type Thirteen = (u8, u8, u8, u8, u8, u8, u8, u8, u8, u8, u8, u8, u8);
+    let sig: Signal<Thirteen, usize> = Signal::new();
+    let counter = Arc::new(AtomicUsize::new(0));
+    connect_counter(&sig, counter.clone());
+    sig.connect_tuple_group_position(|args: Thirteen| (args.0 + args.12) as usize, Group::Front, Position::Front);
+    sig.connect_tuple_extended(|conn, _| {
+        conn.disconnect();
+        100
+    });
+
+    let args: Thirteen = (1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 2);
+    assert_eq!(sig.emit_tuple(args), Some(100));
+    assert_eq!(sig.emit_tuple(args), Some(2));
+    assert_eq!(sig.emit_tuple_parallel(args), Some(3));
+    assert_eq!(sig.get_emit_handle().emit_tuple(args), Some(Some(4)));
+    assert_eq!(counter.load(Ordering::SeqCst), 4);
+
+    let sig: Signal<(i32, i32), usize> = Signal::new();
+    connect_counter(&sig.get_connect_handle(), counter.clone());
+    sig.connect(|x, y| (x + y) as usize);
+    assert_eq!(sig.emit(2, 3), Some(5));
+    assert_eq!(sig.emit_tuple((4, 5)), Some(9));
+
+    let sig: ReplaySignal<(i32,), usize> = ReplaySignal::new(2);
+    sig.emit_tuple((1,));
+    sig.emit(2);
+    connect_counter(&sig, counter.clone());
+    assert_eq!(counter.load(Ordering::SeqCst), 8);
+}
+
+#[test]
+fn connection_set_test() {
+    let sig1: Signal<(), i32> = Signal::new();
+    let sig2: Signal<(i32,), i32> = Signal::new();
+    let connections = ConnectionSet::new();
+    assert!(connections.is_empty());
+
+    connections.add(sig1.connect(|| 1));
+    connections.add(sig2.connect(|x| x));
+    let conn = sig2.connect(|x| x * 2);
+    connections.add(conn.clone());
+    assert_eq!(connections.len(), 3);
+
+    conn.disconnect();
+    assert_eq!(connections.len(), 2);
+    assert_eq!(sig2.emit(3), Some(3));
+
+    let blocker = connections.block_all();
+    connections.add(sig1.connect(|| 2));
+    assert!(blocker.blocking());
+    assert_eq!(sig1.emit(), Some(2));
+    assert_eq!(sig2.emit(3), None);
+
+    blocker.unblock();
+    assert!(!blocker.blocking());
+    assert_eq!(sig2.emit(3), Some(3));
+    blocker.block();
+    assert_eq!(sig2.emit(3), None);
+    mem::drop(blocker);
+    assert_eq!(sig2.emit(3), Some(3));
+
+    connections.disconnect_all();
+    assert!(connections.is_empty());
+    assert_eq!(sig1.count(), 0);
+    assert_eq!(sig2.count(), 0);
+
+    connections.add(sig1.connect(|| 3));
+    mem::drop(connections);
+    assert_eq!(sig1.count(), 0);
+}
+
+#[test]
+fn trackable_test() {
+    #[derive(Clone, Default)]
+    struct Listener {
+        received: Arc<AtomicUsize>,
+        trackable: Trackable
+    }
+
+    impl Listener {
+        fn listen(&self, sig: &Signal<(usize,)>) {
+            let received = self.received.clone();
+            self.trackable.track(sig.connect(move |x| { received.fetch_add(x, Ordering::SeqCst); }));
+        }
+    }
+
+    let sig: Signal<(usize,)> = Signal::new();
+    let listener = Listener::default();
+    listener.listen(&sig);
+    listener.listen(&sig);
+    assert_eq!(listener.trackable.tracked_count(), 2);
+
+    let copy = listener.clone();
+    assert_eq!(copy.trackable.tracked_count(), 0);
+    copy.listen(&sig);
+
+    sig.emit(1);
+    assert_eq!(listener.received.load(Ordering::SeqCst), 3);
+
+    mem::drop(listener);
+    assert_eq!(sig.count(), 1);
+    copy.trackable.disconnect_all();
+    assert_eq!(sig.count(), 0);
+}
+
+#[test]
+fn identity_test() {
+    use std::collections::{HashSet, BTreeSet};
+
+    let sig1: Signal<()> = Signal::new();
+    let sig2: Signal<()> = Signal::new();
+    let clone = sig1.clone();
+    assert!(sig1.ptr_eq(&clone));
+    assert!(!sig1.ptr_eq(&sig2));
+    assert_eq!(sig1.id(), clone.id());
+    assert_ne!(sig1.id(), sig2.id());
+
+    let conn1 = sig1.connect(|| ());
+    let conn2 = clone.connect(|| ());
+    let conn3 = sig2.connect_extended(|_| ());
+    assert_eq!(conn1, conn1.clone());
+    assert_ne!(conn1, conn2);
+    assert!(conn1 < conn2);
+    assert!(conn2.belongs_to(&sig1));
+    assert!(!conn3.belongs_to(&sig1));
+    assert!(conn3.belongs_to(&sig2));
+
+    let untyped: Box<dyn UntypedSignal> = Box::new(sig2.clone());
+    assert!(conn3.belongs_to(untyped.as_ref()));
+
+    let set: HashSet<Connection> = vec!(conn1.clone(), conn2.clone(), conn1.clone()).into_iter().collect();
+    assert_eq!(set.len(), 2);
+    assert!(set.contains(&conn2));
+
+    let id = conn1.id();
+    conn1.disconnect();
+    assert_eq!(conn1.id(), id);
+    assert!(!set.contains(&conn3));
+
+    let scoped = conn2.clone().scoped();
+    assert_eq!(scoped.id(), conn2.id());
+    let scoped_set: BTreeSet<ScopedConnection> = vec!(scoped).into_iter().collect();
+    assert_eq!(sig1.count(), 1);
+    mem::drop(scoped_set);
+    assert_eq!(sig1.count(), 0);
+
+    let debug = format!("{:?}", conn3);
+    assert!(debug.starts_with("Connection {"));
+    assert!(debug.contains(&format!("id: {}", conn3.id())));
+
+    let dead = sig1.get_connect_handle();
+    mem::drop(sig1);
+    mem::drop(clone);
+    let empty1 = dead.connect(|| ());
+    let empty2 = dead.connect(|| ());
+    assert_ne!(empty1, empty2);
+    assert!(!empty1.belongs_to(&sig2));
+}
+
+#[test]
+fn transaction_test() {
+    let sig: Signal<(i32,), i32, SumCombiner> = Signal::new();
+    let conn1 = sig.connect(|x| x);
+    let conn2 = sig.connect(|x| 10 * x);
+    let other: Signal<(i32,), i32, SumCombiner> = Signal::new();
+    let foreign = other.connect(|x| x);
+
+    let conn3 = sig.transaction(|tx| {
+        assert!(tx.disconnect(&conn1));
+        assert!(!tx.disconnect(&conn1));
+        assert!(!tx.disconnect(&foreign));
+        let conn3 = tx.connect(|x| 100 * x);
+        assert_eq!(tx.count(), 2);
+        conn3
+    });
+
+    assert!(!conn1.connected());
+    assert!(conn2.connected() && conn3.connected());
+    assert!(foreign.connected());
+    assert_eq!(sig.count(), 2);
+    assert_eq!(sig.emit(1), 110);
+
+    sig.transaction(|tx| {
+        tx.set_combiner(SumCombiner::default());
+        tx.connect_position(|x| 1000 * x, Position::Front);
+    });
+    assert_eq!(sig.emit(1), 1110);
+
+    sig.transaction(|tx| tx.clear());
+    assert_eq!(sig.count(), 0);
+    assert!(!conn2.connected() && !conn3.connected());
+
+    let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+        sig.transaction(|tx| {
+            tx.connect(|x| x);
+            panic!("rolled back");
+        })
+    }));
+    assert!(result.is_err());
+    assert_eq!(sig.count(), 0);
+    assert_eq!(sig.emit(1), 0);
+
+    let scoped = sig.transaction(|tx| tx.connect(|x| x).scoped());
+    assert_eq!(sig.count(), 1);
+    mem::drop(scoped);
+    assert_eq!(sig.count(), 0);
+}
+
+#[test]
+fn signal_spy_test() {
+    let sig: Signal<(i32, i32), i32> = Signal::new();
+    sig.connect(|x, y| x + y);
+    let spy = SignalSpy::new(&sig);
+    spy.assert_not_emitted();
+    assert_eq!(spy.last(), None);
+
+    assert_eq!(sig.emit(1, 2), Some(3));
+    assert_eq!(sig.emit(3, 4), Some(7));
+    assert_eq!(spy.count(), 2);
+    assert_eq!(spy.last(), Some((3, 4)));
+    spy.assert_emitted_with(&(1, 2));
+
+    let emissions = spy.take();
+    assert_eq!(emissions.len(), 2);
+    assert_eq!((emissions[0].seq, emissions[1].seq), (0, 1));
+    assert!(emissions[0].timestamp <= emissions[1].timestamp);
+    assert_eq!(spy.count(), 0);
+
+    let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| spy.assert_emitted_with(&(1, 2))));
+    assert!(result.is_err());
+
+    let emit_handle = sig.get_emit_handle();
+    let thread = thread::spawn(move || {
+        for i in 0..3 {
+            thread::sleep(Duration::from_millis(10));
+            emit_handle.emit(i, i);
+        }
+    });
+
+    assert!(spy.wait_for(3, Duration::from_secs(5)));
+    thread.join().unwrap();
+    assert_eq!(spy.emissions().iter().map(|emission| emission.seq).collect::<Vec<_>>(), vec!(2, 3, 4));
+    assert!(!spy.wait_for(4, Duration::from_millis(10)));
+
+    assert_eq!(sig.count(), 2);
+    mem::drop(spy);
+    assert_eq!(sig.count(), 1);
+}
+
+#[test]
+fn mock_slot_test() {
+    let sig: Signal<(i32,), i32, SumCombiner> = Signal::new();
+    let seq = Sequence::new();
+    let front = MockSlot::new().named("front").returning(|(x,)| x).times(1).in_sequence(&seq);
+    let named = MockSlot::new().named("named").returning(|(x,)| 10 * x).times(1).in_sequence(&seq);
+    let back = MockSlot::new().named("back").returning(|(x,)| 100 * x).times(1).in_sequence(&seq);
+
+    sig.connect_group(back.slot(), Group::Back);
+    sig.connect_group(named.slot(), Group::Named(0));
+    sig.connect_tuple_group(front.tuple_slot(), Group::Front);
+
+    assert_eq!(sig.emit(2), 222);
+    assert_eq!(named.calls(), vec!((2,)));
+    assert_eq!(back.call_count(), 1);
+    front.verify();
+
+    let out_of_order = Sequence::new();
+    let first: MockSlot<(i32,), i32> = MockSlot::new().named("first").in_sequence(&out_of_order);
+    let second: MockSlot<(i32,), i32> = MockSlot::new().named("second").in_sequence(&out_of_order);
+    let sig: Signal<(i32,), i32> = Signal::new();
+    sig.connect(second.slot());
+    sig.connect(first.slot());
+    sig.emit(0);
+    second.verify();
+    let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| first.verify()));
+    assert!(result.is_err());
+    mem::forget(first);
+
+    let result = std::panic::catch_unwind(|| {
+        let once: MockSlot<(), ()> = MockSlot::new().times(1);
+        let sig: Signal<()> = Signal::new();
+        sig.connect(once.slot());
+        sig.emit();
+        sig.emit();
+    });
+    assert!(result.is_err());
+
+    let sig: Signal<(i32, &'static str), usize> = Signal::new();
+    let pair = MockSlot::new().returning(|(x, s): (i32, &str)| x as usize + s.len()).times(1);
+    sig.connect(pair.slot());
+    assert_eq!(sig.emit(1, "two"), Some(4));
+    assert_eq!(pair.calls(), vec!((1, "two")));
+
+    let result = std::panic::catch_unwind(|| {
+        let _never: MockSlot<(), ()> = MockSlot::new().times(1);
+    });
+    assert!(result.is_err());
+}
+
+#[test]
+fn reentrancy_test() {
+    let sig: Signal<(i32,), i32, VecCombiner> = Signal::new();
+    assert_eq!(sig.reentrancy_policy(), ReentrancyPolicy::Allow);
+    assert_eq!(sig.max_depth(), None);
+
+    // emissions are not tracked while nothing depends on their depth
+    let plain: Signal<(), usize> = Signal::new();
+    let plain_handle = plain.weak();
+    plain.connect(move || plain_handle.upgrade().unwrap().depth());
+    assert_eq!(plain.emit(), Some(0));
+    plain.set_max_depth(Some(8));
+    assert_eq!(plain.emit(), Some(1));
+
+    sig.set_max_depth(Some(8));
+    let weak_sig = sig.weak();
+    let nested = Arc::new(std::sync::Mutex::new(Vec::new()));
+    let nested_clone = nested.clone();
+    sig.connect(move |x| {
+        if x == 0 {
+            let handle = weak_sig.upgrade().unwrap();
+            assert_eq!(handle.depth(), 1);
+            nested_clone.lock().unwrap().push(handle.try_emit_tuple((1,)));
+        }
+
+        x
+    });
+
+    assert_eq!(sig.depth(), 0);
+    assert_eq!(sig.emit(0), vec!(0));
+    assert_eq!(nested.lock().unwrap().pop(), Some(Ok(vec!(1))));
+
+    sig.set_reentrancy_policy(ReentrancyPolicy::Skip);
+    assert_eq!(sig.emit(0), vec!(0));
+    assert_eq!(nested.lock().unwrap().pop(), Some(Err(ReentrancyError::Skipped)));
+
+    let spy = SignalSpy::new(&sig);
+    sig.set_reentrancy_policy(ReentrancyPolicy::Queue);
+    assert_eq!(sig.emit(0), vec!(0));
+    assert_eq!(nested.lock().unwrap().pop(), Some(Err(ReentrancyError::Queued)));
+    assert_eq!(spy.take().into_iter().map(|emission| emission.args).collect::<Vec<_>>(), vec!((0,), (1,)));
+    assert_eq!(sig.try_emit_tuple((2,)), Ok(vec!(2)));
+
+    // a queued emission reaches slots that were connected after it was queued
+    let queued: Signal<(i32,), i32, VecCombiner> = Signal::new();
+    queued.set_reentrancy_policy(ReentrancyPolicy::Queue);
+    let weak_queued = queued.weak();
+    let late = Arc::new(std::sync::Mutex::new(Vec::new()));
+    let late_clone = late.clone();
+    queued.connect(move |x| {
+        if x == 0 {
+            let handle = weak_queued.upgrade().unwrap();
+            handle.emit(1);
+            let late_clone = late_clone.clone();
+            handle.connect(move |x| { late_clone.lock().unwrap().push(x); 10 * x });
+        }
+
+        x
+    });
+
+    assert_eq!(queued.emit(0), vec!(0));
+    assert_eq!(*late.lock().unwrap(), vec!(1));
+
+    // a cycle of two signals forwarding to each other is cut off by the maximum depth
+    let ping: Signal<(u32,), u32, SumCombiner> = Signal::new();
+    let pong: Signal<(u32,), u32, SumCombiner> = Signal::new();
+    let pong_handle = pong.get_emit_handle();
+    let ping_handle = ping.get_emit_handle();
+    ping.connect(move |n| 1 + pong_handle.emit(n + 1).unwrap());
+    pong.connect(move |n| 1 + ping_handle.emit(n + 1).unwrap());
+
+    let refused = Arc::new(AtomicUsize::new(0));
+    let refused_clone = refused.clone();
+    ping.set_max_depth(Some(4));
+    ping.set_max_depth_hook(move |depth| refused_clone.store(depth, Ordering::SeqCst));
+    assert_eq!(ping.emit(0), 8);
+    assert_eq!(refused.load(Ordering::SeqCst), 5);
+    assert_eq!(ping.depth(), 0);
+
+    ping.set_max_depth(Some(0));
+    assert_eq!(ping.try_emit_tuple((0,)), Err(ReentrancyError::MaxDepthExceeded { depth: 1 }));
+
+    // the slots of a parallel emission run at its depth on their worker threads
+    let par: Signal<(u32,), u32, SumCombiner> = Signal::new();
+    let refused = Arc::new(AtomicUsize::new(0));
+    let refused_clone = refused.clone();
+    par.set_max_depth(Some(2));
+    par.set_max_depth_hook(move |_| { refused_clone.fetch_add(1, Ordering::SeqCst); });
+    for _ in 0..4 {
+        let weak_par = par.weak();
+        par.connect(move |n| {
+            let handle = weak_par.upgrade().unwrap();
+            assert_eq!(handle.depth(), n as usize + 1);
+            1 + handle.emit_parallel(n + 1)
+        });
+    }
+
+    assert_eq!(par.emit_parallel(0), 4 + 4 * 4);
+    assert_eq!(refused.load(Ordering::SeqCst), 4 * 4);
+
+    par.set_max_depth(None);
+    par.set_reentrancy_policy(ReentrancyPolicy::Skip);
+    assert_eq!(par.emit_parallel(0), 4);
+}
+
+#[test]
+fn emission_context_test() {
+    let sig: Signal<(i32,), i32, VecCombiner, i32> = Signal::new();
+    let seen = Arc::new(std::sync::Mutex::new(Vec::new()));
+
+    let seen_clone = seen.clone();
+    sig.connect_group_position_with_context(move |ctx: &EmissionContext<i32>, x| {
+        seen_clone.lock().unwrap().push((ctx.sequence(), ctx.depth(), ctx.index(), *ctx.group() == Group::Named(1)));
+        if x < 0 {
+            ctx.stop_propagation();
+        }
+
+        x
+    }, Group::Named(1), Position::Back);
+    sig.connect_group(|x| x * 10, Group::Front);
+    sig.connect(|x| x * 100);
+
+    assert_eq!(sig.emit(1), vec!(10, 1, 100));
+    assert_eq!(sig.emit(-1), vec!(-10, -1));
+    assert_eq!(*seen.lock().unwrap(), vec!((0, 1, 1, true), (1, 1, 1, true)));
+
+    // a slot can disconnect itself once the emission has completed
+    let once: Signal<(), i32, VecCombiner> = Signal::new();
+    once.connect_with_context(|ctx| {
+        ctx.disconnect_after_emission();
+        assert!(ctx.connection().connected());
+        1
+    });
+    once.connect(|| 2);
+    assert_eq!(once.emit(), vec!(1, 2));
+    assert_eq!(once.emit(), vec!(2));
+    assert_eq!(once.count(), 1);
+
+    // slots of later groups are not executed by a parallel emission once propagation has stopped
+    let par: Signal<(i32,), i32, SumCombiner, i32> = Signal::new();
+    par.connect_group_position_with_context(|ctx, x| {
+        ctx.stop_propagation();
+        x
+    }, Group::Named(0), Position::Back);
+    par.connect_group(|x| x * 10, Group::Named(1));
+    assert_eq!(par.emit_parallel_grouped(1), 1);
+    assert_eq!(par.emit(1), 1);
+
+    // within a parallel emission, slots that have not started yet are skipped once propagation has stopped, so at
+    // most one slot per worker thread executes
+    let workers = std::thread::available_parallelism().map_or(2, |n| n.get()).max(2);
+    let stopping: Signal<(), i32, SumCombiner> = Signal::new();
+    for _ in 0..2 * workers {
+        stopping.connect_with_context(|ctx| {
+            ctx.stop_propagation();
+            1
+        });
+    }
+    assert!(stopping.emit_parallel() as usize <= workers);
+
+    // replayed executions receive a context of their own
+    let replay: BehaviorSignal<(i32,), i32> = BehaviorSignal::with_value((5,));
+    let replayed = Arc::new(std::sync::Mutex::new(Vec::new()));
+    let replayed_clone = replayed.clone();
+    replay.connect_with_context(move |ctx, x| {
+        replayed_clone.lock().unwrap().push((ctx.depth(), x));
+        x
+    });
+    replay.emit(6);
+    assert_eq!(*replayed.lock().unwrap(), vec!((1, 5), (1, 6)));
+}
+
+#[test]
+fn retaining_slot_test() {
+    let sig: Signal<(i32,), i32, VecCombiner> = Signal::new();
+    let calls = Arc::new(std::sync::Mutex::new(Vec::new()));
+
+    let calls_clone = calls.clone();
+    let conn = sig.connect_retaining(move |x| {
+        calls_clone.lock().unwrap().push(x);
+        match x {
+            0 => SlotControl::Disconnect,
+            1 => SlotControl::Block(Duration::from_millis(100)),
+            _ => SlotControl::Keep
+        }
+    });
+    sig.connect(|x| x);
+
+    // retaining slots do not contribute to the combiner
+    assert_eq!(sig.emit(2), vec!(2));
+    assert_eq!(sig.emit(1), vec!(1));
+    assert_eq!(sig.emit(3), vec!(3));
+    assert!(conn.connected());
+    thread::sleep(Duration::from_millis(150));
+    sig.emit(4);
+    sig.emit(0);
+    sig.emit(5);
+    assert!(!conn.connected());
+    assert_eq!(*calls.lock().unwrap(), vec!(2, 1, 4, 0));
+
+    // a slot connected to a replay signal can disconnect itself during the replay
+    let replay: ReplaySignal<(i32,)> = ReplaySignal::new(3);
+    replay.emit(1);
+    replay.emit(2);
+    replay.emit(3);
+    let replayed = Arc::new(std::sync::Mutex::new(Vec::new()));
+    let replayed_clone = replayed.clone();
+    let conn = replay.connect_retaining(move |x| {
+        replayed_clone.lock().unwrap().push(x);
+        if x == 2 { SlotControl::Disconnect } else { SlotControl::Keep }
+    });
+
+    replay.emit(4);
+    assert!(!conn.connected());
+    assert_eq!(*replayed.lock().unwrap(), vec!(1, 2));
+}
+
+#[test]
+fn slot_adapter_test() {
+    fn product(x: i32, y: i32) -> i32 {
+        x * y
+    }
+
+    let sig: Signal<(i32, i32, &'static str), i32, VecCombiner> = Signal::new();
+    sig.connect_ignore_args(|| 1);
+    sig.connect_first(|x| x + 10);
+    sig.connect_map(|(x, y, _)| (y, x), |y: i32, x: i32| y - x);
+    sig.connect_map(|(x, y, _)| (x, y), product);
+    sig.connect_map(|(_, _, s): (i32, i32, &str)| (s.len(),), |len: usize| len as i32);
+    assert_eq!(sig.emit(2, 5, "four"), vec!(1, 12, 3, 10, 4));
+
+    let unit: Signal<(), i32, VecCombiner> = Signal::new();
+    unit.connect_ignore_args(|| 1);
+    unit.connect_map(|()| (2, 3), product);
+    assert_eq!(unit.emit(), vec!(1, 6));
+
+    let handle = sig.get_connect_handle();
+    handle.connect_first(|x| x);
+    assert_eq!(sig.emit(7, 0, "").last(), Some(&7));
+}
+
+#[test]
+fn method_binding_test() {
+    struct Accumulator {
+        total: AtomicUsize
+    }
+
+    impl Accumulator {
+        fn reset(&self) {
+            self.total.store(0, Ordering::SeqCst);
+        }
+
+        fn add(&self, x: usize, y: usize) -> usize {
+            self.total.fetch_add(x + y, Ordering::SeqCst) + x + y
+        }
+    }
+
+    let acc = Arc::new(Accumulator { total: AtomicUsize::new(0) });
+    let sig: Signal<(usize, usize), usize, VecCombiner> = Signal::new();
+    let reset: Signal<()> = Signal::new();
+
+    sig.connect_method(&acc, Accumulator::add);
+    reset.connect_method(&acc, Accumulator::reset);
+    assert_eq!(sig.emit(1, 2), vec!(3));
+    assert_eq!(Arc::strong_count(&acc), 3);
+    reset.emit();
+    assert_eq!(acc.total.load(Ordering::SeqCst), 0);
+
+    struct TrackedAccumulator {
+        acc: Accumulator,
+        trackable: Trackable
+    }
+
+    impl AsRef<Trackable> for TrackedAccumulator {
+        fn as_ref(&self) -> &Trackable {
+            &self.trackable
+        }
+    }
+
+    let weak_acc = Arc::new(TrackedAccumulator { acc: Accumulator { total: AtomicUsize::new(0) }, trackable: Trackable::new() });
+    let conn = sig.get_connect_handle().connect_weak_method(&weak_acc, |tracked: &TrackedAccumulator, x, y| tracked.acc.add(x, y));
+    assert_eq!(Arc::strong_count(&weak_acc), 1);
+    assert_eq!(weak_acc.trackable.tracked_count(), 1);
+    assert_eq!(sig.emit(2, 2), vec!(4, 4));
+
+    // the slot is disconnected as soon as the receiver is dropped
+    mem::drop(weak_acc);
+    assert!(!conn.connected());
+    assert_eq!(sig.count(), 1);
+    assert_eq!(sig.emit(1, 1), vec!(6));
+}
@@ -0,0 +1,78 @@
+// Copyright Christian Daley 2021
+// Copyright Frank Mori Hess 2007-2008.
+// Distributed under the Boost Software License, Version 1.0. 
+// See http://www.boost.org/LICENSE_1_0.txt
+
+#![cfg(feature = "serde")]
+
+use signals2::*;
+use signals2::record::{EmissionRecorder, Replayer, Format, Timing, RecordError, MAX_FRAME_SIZE};
+use std::io::{self, Write};
+use std::thread;
+use std::time::{Duration, Instant};
+
+struct FailingWriter;
+
+impl Write for FailingWriter {
+    fn write(&mut self, _buf: &[u8]) -> io::Result<usize> {
+        Err(io::Error::new(io::ErrorKind::Other, "disk full"))
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+#[test]
+fn record_replay_test() {
+    for &format in [Format::JsonLines, Format::Binary].iter() {
+        let sig: Signal<(i32, String), i32> = Signal::new();
+        sig.connect(|x, _| x);
+        let recorder = EmissionRecorder::new(&sig, Vec::new(), format);
+        assert_eq!(sig.emit(1, "one".to_string()), Some(1));
+        thread::sleep(Duration::from_millis(50));
+        sig.emit(2, "two".to_string());
+        assert_eq!(recorder.count(), 2);
+        assert_eq!(sig.count(), 2);
+
+        let log = recorder.finish().unwrap();
+        assert_eq!(sig.count(), 1);
+
+        let replayer: Replayer<(i32, String)> = Replayer::read(log.as_slice(), format).unwrap();
+        let emissions = replayer.emissions();
+        assert_eq!(emissions.len(), 2);
+        assert_eq!((emissions[0].seq, emissions[1].seq), (0, 1));
+        assert_eq!(emissions[1].args, (2, "two".to_string()));
+        assert!(emissions[1].elapsed - emissions[0].elapsed >= Duration::from_millis(50));
+
+        let target: Signal<(i32, String), i32> = Signal::new();
+        target.connect(|x, _| 10 * x);
+        assert_eq!(replayer.replay(&target, Timing::Fast), vec!(Some(10), Some(20)));
+
+        let start = Instant::now();
+        let handle = target.get_emit_handle();
+        assert_eq!(replayer.replay(&handle, Timing::Original), vec!(Some(Some(10)), Some(Some(20))));
+        assert!(start.elapsed() >= Duration::from_millis(50));
+    }
+}
+
+#[test]
+fn record_error_test() {
+    let sig: Signal<(i32,)> = Signal::new();
+    let recorder = EmissionRecorder::new(&sig, FailingWriter, Format::Binary);
+    sig.emit(1);
+    sig.emit(2);
+    assert_eq!(recorder.count(), 0);
+    assert!(matches!(recorder.finish(), Err(RecordError::Io(_))));
+
+    // an oversized length prefix is rejected without allocating the frame
+    let oversized = (MAX_FRAME_SIZE as u32 + 1).to_le_bytes();
+    match Replayer::<(i32,)>::read(&oversized[..], Format::Binary) {
+        Err(RecordError::Io(err)) => assert_eq!(err.kind(), io::ErrorKind::InvalidData),
+        _ => panic!("expected an InvalidData error")
+    }
+
+    let truncated = [5u8, 0, 0, 0, 1];
+    assert!(matches!(Replayer::<(i32,)>::read(&truncated[..], Format::Binary), Err(RecordError::Io(_))));
+    assert!(matches!(Replayer::<(i32,)>::read(&b"not json\n"[..], Format::JsonLines), Err(RecordError::Json(_))));
+}
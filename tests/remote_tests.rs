@@ -0,0 +1,103 @@
+// Copyright Christian Daley 2021
+// Copyright Frank Mori Hess 2007-2008.
+// Distributed under the Boost Software License, Version 1.0. 
+// See http://www.boost.org/LICENSE_1_0.txt
+
+#![cfg(feature = "serde")]
+
+use signals2::*;
+use signals2::remote::{Transport, MemoryTransport, TcpTransport, RemotePublisher, RemoteSignal};
+use std::io::{self, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::{Mutex, mpsc};
+use std::thread;
+use std::mem;
+use std::time::{Duration, Instant};
+
+// Consumers only depend on the connect traits, so they work with both local and remote signals.
+fn forward_sums<S: Connect2<(), combiner::DefaultCombiner, i32, i32, i32>>(sig: &S) -> mpsc::Receiver<i32> {
+    let (sender, receiver) = mpsc::channel();
+    let sender = Mutex::new(sender);
+    sig.connect(move |x, y| { let _ = sender.lock().unwrap().send(x + y); });
+    receiver
+}
+
+#[test]
+fn memory_transport_test() {
+    let (end1, end2) = MemoryTransport::pair();
+    end1.send_frame(b"hello").unwrap();
+    assert_eq!(end2.recv_frame().unwrap(), Some(b"hello".to_vec()));
+
+    end2.close();
+    assert_eq!(end1.recv_frame().unwrap(), None);
+    assert!(end2.send_frame(b"closed").is_err());
+}
+
+#[test]
+fn remote_signal_memory_test() {
+    let (publisher_end, signal_end) = MemoryTransport::pair();
+    let local: Signal<(i32, i32)> = Signal::new();
+    let publisher = RemotePublisher::new(&local, publisher_end);
+
+    let remote: RemoteSignal<(i32, i32)> = RemoteSignal::new(signal_end);
+    let local_sums = forward_sums(&local);
+    let remote_sums = forward_sums(&remote);
+    assert_eq!(remote.count(), 1);
+
+    local.emit(1, 2);
+    local.emit(3, 4);
+    assert_eq!(local_sums.recv_timeout(Duration::from_secs(5)), Ok(3));
+    assert_eq!(remote_sums.recv_timeout(Duration::from_secs(5)), Ok(3));
+    assert_eq!(remote_sums.recv_timeout(Duration::from_secs(5)), Ok(7));
+    assert!(remote.is_open());
+
+    // the publisher disconnects itself once the remote end is gone
+    mem::drop(remote);
+    local.emit(5, 6);
+    assert!(!publisher.connected());
+    assert_eq!(local.count(), 1);
+}
+
+#[test]
+fn remote_signal_tcp_test() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    let server = thread::spawn(move || {
+        let (stream, _) = listener.accept().unwrap();
+        let remote: RemoteSignal<(String,), usize, combiner::SumCombiner> = RemoteSignal::new(TcpTransport::new(stream).unwrap());
+        let (sender, receiver) = mpsc::channel();
+        let sender = Mutex::new(sender);
+        remote.connect(move |s: String| { sender.lock().unwrap().send(s.clone()).unwrap(); s.len() });
+        remote.get_connect_handle().connect(|s| 2 * s.len());
+
+        let received: Vec<String> = receiver.iter().take(2).collect();
+
+        // the remote signal stops once the publisher closes the connection
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while remote.is_open() && Instant::now() < deadline {
+            thread::sleep(Duration::from_millis(5));
+        }
+
+        (received, remote.is_open())
+    });
+
+    let local: Signal<(String,)> = Signal::new();
+    let publisher = RemotePublisher::new(&local, TcpTransport::connect(addr).unwrap());
+    local.emit("hello".to_string());
+    local.emit("world".to_string());
+    mem::drop(publisher);
+
+    let (received, open) = server.join().unwrap();
+    assert_eq!(received, vec!("hello".to_string(), "world".to_string()));
+    assert!(!open);
+}
+
+#[test]
+fn tcp_transport_oversized_frame_test() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let mut stream = TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+    let transport = TcpTransport::new(listener.accept().unwrap().0).unwrap();
+
+    stream.write_all(&u32::MAX.to_le_bytes()).unwrap();
+    assert_eq!(transport.recv_frame().unwrap_err().kind(), io::ErrorKind::InvalidData);
+}
@@ -0,0 +1,72 @@
+// Copyright Christian Daley 2021
+// Copyright Frank Mori Hess 2007-2008.
+// Distributed under the Boost Software License, Version 1.0. 
+// See http://www.boost.org/LICENSE_1_0.txt
+
+#![cfg(feature = "futures")]
+
+use signals2::*;
+use signals2::stream::{OverflowPolicy, SinkError};
+use futures::{executor, SinkExt, StreamExt};
+use std::thread;
+use std::mem;
+
+#[test]
+fn stream_test() {
+    let sig: Signal<(i32, i32), i32> = Signal::new();
+    sig.connect(|x, y| x + y);
+
+    let mut stream = sig.stream(8, OverflowPolicy::DropOldest);
+    assert_eq!(sig.count(), 2);
+    assert_eq!(sig.emit(1, 2), Some(3));
+
+    let emit_handle = sig.get_emit_handle();
+    let thread = thread::spawn(move || {
+        for i in 0..5 {
+            emit_handle.emit(i, i);
+        }
+    });
+
+    executor::block_on(async {
+        assert_eq!(stream.next().await, Some((1, 2)));
+        let sums: Vec<i32> = (&mut stream).take(5).map(|(x, y)| x + y).collect().await;
+        assert_eq!(sums, vec!(0, 2, 4, 6, 8));
+    });
+
+    thread.join().unwrap();
+    mem::drop(stream);
+    assert_eq!(sig.count(), 1);
+}
+
+#[test]
+fn stream_overflow_test() {
+    let sig: Signal<(i32,)> = Signal::new();
+    let newest = sig.stream(3, OverflowPolicy::DropNewest);
+    let oldest = sig.stream(3, OverflowPolicy::DropOldest);
+
+    for i in 0..6 {
+        sig.emit(i);
+    }
+
+    sig.clear();
+    assert_eq!(executor::block_on(newest.collect::<Vec<_>>()), vec!((0,), (1,), (2,)));
+    assert_eq!(executor::block_on(oldest.collect::<Vec<_>>()), vec!((3,), (4,), (5,)));
+}
+
+#[test]
+fn emit_handle_sink_test() {
+    let sig: Signal<(String,)> = Signal::new();
+    let receiver = sig.receiver();
+    let mut sink = sig.get_emit_handle();
+
+    executor::block_on(async {
+        let mut items = futures::stream::iter(vec!(("a".to_string(),), ("b".to_string(),))).map(Ok);
+        sink.send_all(&mut items).await.unwrap();
+    });
+
+    assert_eq!(receiver.try_recv(), Ok(("a".to_string(),)));
+    assert_eq!(receiver.try_recv(), Ok(("b".to_string(),)));
+
+    mem::drop(sig);
+    assert_eq!(executor::block_on(sink.send(("c".to_string(),))), Err(SinkError));
+}
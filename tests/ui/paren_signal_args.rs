@@ -0,0 +1,11 @@
+#![allow(unused_parens)]
+
+use signals2::*;
+
+#[derive(Default, Signals)]
+struct Widget {
+    #[signal]
+    resized: Signal<(u32)>
+}
+
+fn main() {}